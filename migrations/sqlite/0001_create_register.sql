@@ -0,0 +1,11 @@
+-- The composite primary key doubles as the index for looking up every user
+-- registered against a bot, as `fetch` does.
+CREATE TABLE IF NOT EXISTS register (
+    bot_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    entry_version INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (bot_id, user_id)
+);
+
+-- Used by `list` to find every bot a user has registered.
+CREATE INDEX IF NOT EXISTS register_user_id_idx ON register (user_id, bot_id);
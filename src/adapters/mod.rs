@@ -1 +1,2 @@
+pub mod orphans;
 pub mod register;
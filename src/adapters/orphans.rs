@@ -0,0 +1,83 @@
+use crate::domain::orphans::{OrphanFlags, OrphanSnapshot};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum OrphanStateError {
+    #[error("Could not access orphan state file - {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse orphan state file - {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Reads the flags saved by the last shutdown, starting over if there are none.
+pub fn load_orphans(path: &Path) -> OrphanFlags {
+    match read(path) {
+        Ok(Some(snapshot)) => {
+            tracing::info!(
+                "Restored {} flagged orphans from {path:?}",
+                snapshot.flagged.len()
+            );
+            OrphanFlags::from(snapshot)
+        }
+        Ok(None) => OrphanFlags::default(),
+        Err(why) => {
+            tracing::warn!("Ignoring orphan state in {path:?} - {why}");
+            OrphanFlags::default()
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<Option<OrphanSnapshot>, OrphanStateError> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(why) if why.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(why) => return Err(why.into()),
+    };
+
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+/// Writes to a temporary file first so a shutdown cut short never leaves a
+/// half written state file behind.
+pub fn save_orphans(orphans: &OrphanFlags, path: &Path) -> Result<(), OrphanStateError> {
+    let contents = serde_json::to_string(&orphans.snapshot())?;
+    let temporary = path.with_extension("tmp");
+
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::orphans::Orphan;
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("orphans-{}.json", std::process::id()));
+        let orphans = OrphanFlags::default();
+        orphans.flag(
+            Orphan::BotGone(String::from("bot_id_12345")),
+            SystemTime::UNIX_EPOCH,
+        );
+        orphans.delivery_failed("user_id_12345");
+
+        save_orphans(&orphans, &path).unwrap();
+        let loaded = load_orphans(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.snapshot(), orphans.snapshot());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let path = std::env::temp_dir().join("orphans-that-do-not-exist.json");
+
+        assert_eq!(load_orphans(&path).snapshot(), OrphanSnapshot::default());
+    }
+}
@@ -0,0 +1,566 @@
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+struct Slot {
+    entries: Vec<RegisterEntry>,
+    fetched_at: Instant,
+}
+
+/// The bots anyone is registered for.
+struct Monitored {
+    bots: HashSet<String>,
+    /// Whether `bots` holds every monitored bot. After a failed load only a
+    /// fetch can tell whether a bot is monitored.
+    complete: bool,
+    loaded_at: Option<Instant>,
+}
+
+/// Keeps the set of monitored bots and their entries in memory, so presence
+/// updates for bots nobody monitors are answered without calling the wrapped
+/// register. Writes through this register keep the set current and drop the
+/// bot's cached entries, and the ttl bounds how stale writes made by other
+/// processes can get before the set and entries are loaded again.
+pub struct Cached<R> {
+    inner: R,
+    ttl: Duration,
+    monitored: RwLock<Monitored>,
+    slots: RwLock<HashMap<String, Slot>>,
+    generation: AtomicU64,
+    loading: tokio::sync::Mutex<()>,
+}
+
+impl<R> Cached<R>
+where
+    R: Register,
+{
+    pub fn new(inner: R, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            monitored: RwLock::new(Monitored {
+                bots: HashSet::new(),
+                complete: false,
+                loaded_at: None,
+            }),
+            slots: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            loading: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Loads the monitored bots from the wrapped register, unless another call
+    /// already has within the ttl.
+    pub async fn load_monitored(&self) {
+        let _loading = self.loading.lock().await;
+        if !self.monitored_is_stale() {
+            return;
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let loaded = self.inner.monitored_bots().await;
+
+        let Ok(mut monitored) = self.monitored.write() else {
+            return;
+        };
+        match loaded {
+            Ok(mut bots) => {
+                // Keep bots added while loading, an extra one only costs a fetch.
+                if generation != self.generation.load(Ordering::SeqCst) {
+                    bots.extend(monitored.bots.drain());
+                }
+                tracing::debug!("Loaded {} monitored bots", bots.len());
+                monitored.bots = bots;
+                monitored.complete = true;
+            }
+            Err(why) => {
+                tracing::warn!("Could not load monitored bots, fetching every bot - {why}");
+                monitored.complete = false;
+            }
+        }
+        monitored.loaded_at = Some(Instant::now());
+    }
+
+    fn monitored_is_stale(&self) -> bool {
+        self.monitored.read().map_or(true, |monitored| {
+            monitored
+                .loaded_at
+                .is_none_or(|loaded_at| loaded_at.elapsed() >= self.ttl)
+        })
+    }
+
+    /// Whether anyone is registered for the bot, `None` when only a fetch can tell.
+    async fn is_monitored(&self, bot_id: &str) -> Option<bool> {
+        if self.monitored_is_stale() {
+            self.load_monitored().await;
+        }
+
+        let monitored = self.monitored.read().ok()?;
+        if monitored.bots.contains(bot_id) {
+            Some(true)
+        } else if monitored.complete {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn set_monitored(&self, bot_id: &str, is_monitored: bool) {
+        if let Ok(mut monitored) = self.monitored.write() {
+            if is_monitored {
+                monitored.bots.insert(String::from(bot_id));
+            } else {
+                monitored.bots.remove(bot_id);
+            }
+        }
+    }
+
+    fn cached(&self, bot_id: &str) -> Option<Vec<RegisterEntry>> {
+        let slots = self.slots.read().ok()?;
+        let slot = slots.get(bot_id)?;
+
+        if slot.fetched_at.elapsed() < self.ttl {
+            Some(slot.entries.clone())
+        } else {
+            None
+        }
+    }
+
+    fn invalidate(&self, bot_id: &str) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut slots) = self.slots.write() {
+            slots.remove(bot_id);
+        }
+    }
+
+    fn invalidate_all(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut slots) = self.slots.write() {
+            slots.clear();
+        }
+    }
+}
+
+#[async_trait]
+impl<R> Register for Cached<R>
+where
+    R: Register + Send + Sync,
+{
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        if self.is_monitored(&bot_id).await == Some(false) {
+            return Ok(Vec::new());
+        }
+        if let Some(entries) = self.cached(&bot_id) {
+            return Ok(entries);
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let entries = self.inner.fetch(bot_id.clone()).await?;
+
+        // A write while we were fetching may have made these entries stale.
+        if generation != self.generation.load(Ordering::SeqCst) {
+            return Ok(entries);
+        }
+        if entries.is_empty() {
+            // Its last user was removed, by this process or another one.
+            self.set_monitored(&bot_id, false);
+        } else if let Ok(mut slots) = self.slots.write() {
+            slots.retain(|_, slot| slot.fetched_at.elapsed() < self.ttl);
+            slots.insert(
+                bot_id.clone(),
+                Slot {
+                    entries: entries.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+            drop(slots);
+            self.set_monitored(&bot_id, true);
+        }
+
+        Ok(entries)
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let bot_id = entry.bot_id.clone();
+        let result = self.inner.add(entry).await;
+        self.invalidate(&bot_id);
+        if matches!(result, Ok(()) | Err(RegisterError::AlreadyRegistered)) {
+            self.set_monitored(&bot_id, true);
+        }
+        result
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let bot_id = entry.bot_id.clone();
+        let result = self.inner.update(entry).await;
+        self.invalidate(&bot_id);
+        result
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        // The bot stays monitored until a fetch finds its last user gone.
+        let bot_id = entry.bot_id.clone();
+        let result = self.inner.remove(entry).await;
+        self.invalidate(&bot_id);
+        result
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        self.inner.list(entry).await
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        let result = self.inner.forget_user(user_id).await;
+        // The user's entries may be spread over any number of bots.
+        self.invalidate_all();
+        result
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        if self.monitored_is_stale() {
+            self.load_monitored().await;
+        }
+        if let Ok(monitored) = self.monitored.read()
+            && monitored.complete
+        {
+            return Ok(monitored.bots.clone());
+        }
+
+        self.inner.monitored_bots().await
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        self.inner.registrants().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+    use mockall::predicate::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn entries(bot_id: &str) -> Vec<RegisterEntry> {
+        vec![RegisterEntry {
+            bot_id: String::from(bot_id),
+            user_id: String::from("user_id_12345"),
+        }]
+    }
+
+    fn monitoring(register: &mut MockRegister, bot_ids: &[&str]) {
+        let bots: HashSet<String> = bot_ids.iter().map(|bot_id| String::from(*bot_id)).collect();
+        register
+            .expect_monitored_bots()
+            .times(1)
+            .return_const(Ok(bots));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_is_cached() {
+        let bot_id = String::from("bot_id_12345");
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(bot_id.clone()))
+            .return_const(Ok(entries(&bot_id)));
+
+        let cached = Cached::new(register, TTL);
+
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(entries(&bot_id)));
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(entries(&bot_id)));
+    }
+
+    #[tokio::test]
+    async fn test_unmonitored_bot_is_not_fetched() {
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register.expect_fetch().never();
+
+        let cached = Cached::new(register, TTL);
+        cached.load_monitored().await;
+
+        for bot_id in ["bot_id_0", "bot_id_1", "bot_id_0"] {
+            assert_eq!(cached.fetch(String::from(bot_id)).await, Ok(Vec::new()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_failure_fetches_every_bot() {
+        let bot_id = String::from("bot_id_12345");
+
+        let mut register = MockRegister::new();
+        register.expect_monitored_bots().times(1).return_const(Err(
+            RegisterError::EntryFetchError(StorageError::retryable("timed out")),
+        ));
+        register
+            .expect_fetch()
+            .times(2)
+            .return_const(Ok(Vec::new()));
+
+        let cached = Cached::new(register, TTL);
+        cached.load_monitored().await;
+
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(Vec::new()));
+        assert_eq!(
+            cached.fetch(String::from("bot_id_67890")).await,
+            Ok(Vec::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_monitored_bots_from_memory() {
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register.expect_add().times(1).return_const(Ok(()));
+
+        let cached = Cached::new(register, TTL);
+        cached.load_monitored().await;
+        cached
+            .add(CreateEntry {
+                user_id: String::from("user_id_12345"),
+                bot_id: String::from("bot_id_67890"),
+                version: 0,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            cached.monitored_bots().await,
+            Ok(HashSet::from([
+                String::from("bot_id_12345"),
+                String::from("bot_id_67890")
+            ]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error_is_not_cached() {
+        let bot_id = String::from("bot_id_12345");
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register
+            .expect_fetch()
+            .times(2)
+            .return_const(Err(RegisterError::EntryFetchError(
+                StorageError::retryable("timed out"),
+            )));
+
+        let cached = Cached::new(register, TTL);
+
+        assert!(cached.fetch(bot_id.clone()).await.is_err());
+        assert!(cached.fetch(bot_id.clone()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_expires() {
+        let bot_id = String::from("bot_id_12345");
+
+        let mut register = MockRegister::new();
+        register
+            .expect_monitored_bots()
+            .times(2)
+            .returning(|| Ok(HashSet::from([String::from("bot_id_12345")])));
+        register
+            .expect_fetch()
+            .times(2)
+            .return_const(Ok(entries(&bot_id)));
+
+        let cached = Cached::new(register, Duration::ZERO);
+
+        cached.fetch(bot_id.clone()).await.unwrap();
+        cached.fetch(bot_id.clone()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_evicted() {
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_0", "bot_id_1"]);
+        register
+            .expect_fetch()
+            .times(2)
+            .returning(|bot_id| Ok(entries(&bot_id)));
+
+        let cached = Cached::new(register, TTL);
+        cached.fetch(String::from("bot_id_0")).await.unwrap();
+        cached
+            .slots
+            .write()
+            .unwrap()
+            .get_mut("bot_id_0")
+            .unwrap()
+            .fetched_at -= TTL;
+        cached.fetch(String::from("bot_id_1")).await.unwrap();
+
+        let slots = cached.slots.read().unwrap();
+        assert_eq!(slots.keys().collect::<Vec<_>>(), vec!["bot_id_1"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_monitors_bot() {
+        let bot_id = String::from("bot_id_12345");
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: bot_id.clone(),
+            version: 0,
+        };
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &[]);
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(entry.clone()))
+            .return_const(Ok(()));
+        register
+            .expect_fetch()
+            .times(1)
+            .return_const(Ok(entries(&bot_id)));
+
+        let cached = Cached::new(register, TTL);
+
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(Vec::new()));
+        cached.add(entry).await.unwrap();
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(entries(&bot_id)));
+    }
+
+    #[tokio::test]
+    async fn test_add_invalidates() {
+        let bot_id = String::from("bot_id_12345");
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: bot_id.clone(),
+            version: 0,
+        };
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register
+            .expect_fetch()
+            .times(2)
+            .return_const(Ok(entries(&bot_id)));
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(entry.clone()))
+            .return_const(Ok(()));
+
+        let cached = Cached::new(register, TTL);
+
+        cached.fetch(bot_id.clone()).await.unwrap();
+        cached.add(entry).await.unwrap();
+        cached.fetch(bot_id.clone()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_remove_invalidates() {
+        let bot_id = String::from("bot_id_12345");
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: bot_id.clone(),
+        };
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register
+            .expect_fetch()
+            .times(2)
+            .return_const(Ok(entries(&bot_id)));
+        register
+            .expect_remove()
+            .times(1)
+            .with(eq(entry.clone()))
+            .return_const(Ok(()));
+
+        let cached = Cached::new(register, TTL);
+
+        cached.fetch(bot_id.clone()).await.unwrap();
+        cached.remove(entry).await.unwrap();
+        cached.fetch(bot_id.clone()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_last_user_removed() {
+        let bot_id = String::from("bot_id_12345");
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: bot_id.clone(),
+        };
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345"]);
+        register.expect_remove().times(1).return_const(Ok(()));
+        register
+            .expect_fetch()
+            .times(1)
+            .return_const(Ok(Vec::new()));
+
+        let cached = Cached::new(register, TTL);
+
+        cached.remove(entry).await.unwrap();
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(Vec::new()));
+        assert_eq!(cached.fetch(bot_id.clone()).await, Ok(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_other_bots_stay_cached() {
+        let bot_id = String::from("bot_id_12345");
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_67890"),
+        };
+
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_12345", "bot_id_67890"]);
+        register
+            .expect_fetch()
+            .times(1)
+            .return_const(Ok(entries(&bot_id)));
+        register.expect_remove().times(1).return_const(Ok(()));
+
+        let cached = Cached::new(register, TTL);
+
+        cached.fetch(bot_id.clone()).await.unwrap();
+        cached.remove(entry).await.unwrap();
+        cached.fetch(bot_id.clone()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_forget_user_invalidates_all() {
+        let mut register = MockRegister::new();
+        monitoring(&mut register, &["bot_id_0", "bot_id_1"]);
+        register
+            .expect_fetch()
+            .times(4)
+            .returning(|bot_id| Ok(entries(&bot_id)));
+        register
+            .expect_forget_user()
+            .times(1)
+            .with(eq(String::from("user_id_12345")))
+            .return_const(Ok(2));
+
+        let cached = Cached::new(register, TTL);
+
+        for bot_id in ["bot_id_0", "bot_id_1"] {
+            cached.fetch(String::from(bot_id)).await.unwrap();
+        }
+        cached
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap();
+        for bot_id in ["bot_id_0", "bot_id_1"] {
+            cached.fetch(String::from(bot_id)).await.unwrap();
+        }
+    }
+}
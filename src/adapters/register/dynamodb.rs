@@ -1,24 +1,316 @@
 use crate::domain::events::create::CreateEntry;
 use crate::domain::events::list::ListEntriesPayload;
 use crate::domain::events::remove::RemoveEntry;
-use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use crate::domain::register::{Register, RegisterEntry, RegisterError, StorageError};
 use async_trait::async_trait;
 use aws_sdk_dynamodb::Client;
-use aws_sdk_dynamodb::types::AttributeValue;
-use std::env;
+use aws_sdk_dynamodb::config::http::HttpResponse;
+use aws_sdk_dynamodb::config::retry::RetryConfig;
+use aws_sdk_dynamodb::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_dynamodb::types::{
+    AttributeDefinition, AttributeValue, BillingMode, CreateGlobalSecondaryIndexAction,
+    GlobalSecondaryIndex, GlobalSecondaryIndexUpdate, IndexStatus, KeySchemaElement, KeyType,
+    Projection, ProjectionType, ProvisionedThroughput, ScalarAttributeType, TableDescription,
+    TableStatus,
+};
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Error codes DynamoDB uses for throttling and transient faults on its side.
+const RETRYABLE_CODES: [&str; 6] = [
+    "ProvisionedThroughputExceededException",
+    "ThrottlingException",
+    "RequestLimitExceeded",
+    "InternalServerError",
+    "ServiceUnavailable",
+    "TransactionConflictException",
+];
+
+/// The index `list` queries entries by user through.
+const USER_ID_INDEX: &str = "user_id-index";
+
+const BOOTSTRAP_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const BOOTSTRAP_TIMEOUT: Duration = Duration::from_secs(300);
 
 pub struct DynamoDB(Client, String);
 
+#[derive(Error, Debug)]
+pub enum BootstrapError {
+    #[error("DynamoDB request failed - {0}")]
+    Request(#[source] Box<dyn StdError + Send + Sync>),
+    #[error("table {table} does not match the register's schema - {reason}")]
+    Schema { table: String, reason: String },
+    #[error("table {0} did not become active in time")]
+    Timeout(String),
+}
+
+impl BootstrapError {
+    fn request(why: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::Request(why.into())
+    }
+}
+
+fn key(attribute_name: &str, key_type: KeyType) -> Result<KeySchemaElement, BootstrapError> {
+    KeySchemaElement::builder()
+        .attribute_name(attribute_name)
+        .key_type(key_type)
+        .build()
+        .map_err(BootstrapError::request)
+}
+
+fn string_attribute(attribute_name: &str) -> Result<AttributeDefinition, BootstrapError> {
+    AttributeDefinition::builder()
+        .attribute_name(attribute_name)
+        .attribute_type(ScalarAttributeType::S)
+        .build()
+        .map_err(BootstrapError::request)
+}
+
+fn is_active(table: &TableDescription) -> bool {
+    table.table_status() == Some(&TableStatus::Active)
+        && table
+            .global_secondary_indexes()
+            .iter()
+            .all(|index| index.index_status() == Some(&IndexStatus::Active))
+}
+
+fn user_id_index() -> Result<(Vec<KeySchemaElement>, Projection), BootstrapError> {
+    Ok((
+        vec![
+            key("user_id", KeyType::Hash)?,
+            key("bot_id", KeyType::Range)?,
+        ],
+        Projection::builder()
+            .projection_type(ProjectionType::All)
+            .build(),
+    ))
+}
+
+fn has_key(schema: &[KeySchemaElement], attribute_name: &str, key_type: KeyType) -> bool {
+    schema
+        .iter()
+        .any(|key| key.attribute_name() == attribute_name && *key.key_type() == key_type)
+}
+
+fn storage_error<E>(why: SdkError<E, HttpResponse>) -> StorageError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    let retryable = match &why {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            true
+        }
+        SdkError::ServiceError(service_error) => {
+            service_error.raw().status().is_server_error()
+                || service_error
+                    .err()
+                    .code()
+                    .is_some_and(|code| RETRYABLE_CODES.contains(&code))
+        }
+        _ => false,
+    };
+
+    if retryable {
+        StorageError::retryable(why)
+    } else {
+        StorageError::permanent(why)
+    }
+}
+
 impl DynamoDB {
-    pub async fn new() -> Self {
-        let shared_config = aws_config::load_from_env().await;
-        Self(Client::new(&shared_config), env::var("TABLE_NAME").unwrap())
+    pub async fn new(table_name: String, endpoint_url: Option<&str>) -> Self {
+        let mut loader = aws_config::from_env();
+        if let Some(endpoint_url) = endpoint_url {
+            loader = loader.endpoint_url(endpoint_url);
+        }
+
+        let shared_config = loader.load().await;
+        Self(Client::new(&shared_config), table_name)
+    }
+
+    /// Turns off the SDK's own retries, for when the register is wrapped in
+    /// `Resilient` so every attempt goes through its backoff and breaker.
+    pub fn without_retries(self) -> Self {
+        let config = self
+            .0
+            .config()
+            .to_builder()
+            .retry_config(RetryConfig::disabled())
+            .build();
+        Self(Client::from_conf(config), self.1)
+    }
+
+    /// Every value the attribute has, reading the whole table.
+    async fn scan_distinct(&self, attribute_name: &str) -> Result<HashSet<String>, RegisterError> {
+        let scan_op = self
+            .0
+            .scan()
+            .table_name(&self.1)
+            .projection_expression(attribute_name)
+            .into_paginator()
+            .items()
+            .send()
+            .try_collect()
+            .await;
+
+        let results = match scan_op {
+            Err(e) => {
+                tracing::error!("{e:?}");
+                return Err(RegisterError::EntryFetchError(storage_error(e)));
+            }
+            Ok(items) => items,
+        };
+
+        Ok(results
+            .into_iter()
+            .filter_map(|value| match value.get(attribute_name)? {
+                AttributeValue::S(value) => Some(value.clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Checks the table and its user id index are keyed the way the register
+    /// queries them, creating whichever is missing and waiting until it is active.
+    pub async fn bootstrap(&self) -> Result<(), BootstrapError> {
+        let Some(table) = self.describe().await? else {
+            tracing::info!("Creating DynamoDB table {}", self.1);
+            self.create_table().await?;
+            return self.wait_until_active().await;
+        };
+
+        if !has_key(table.key_schema(), "bot_id", KeyType::Hash)
+            || !has_key(table.key_schema(), "user_id", KeyType::Range)
+        {
+            return Err(self.schema_error("expected bot_id as hash key and user_id as range key"));
+        }
+
+        let index = table
+            .global_secondary_indexes()
+            .iter()
+            .find(|index| index.index_name() == Some(USER_ID_INDEX));
+
+        match index {
+            Some(index) if has_key(index.key_schema(), "user_id", KeyType::Hash) => {
+                if is_active(&table) {
+                    Ok(())
+                } else {
+                    self.wait_until_active().await
+                }
+            }
+            Some(_) => Err(self.schema_error("expected user_id as the hash key of user_id-index")),
+            None => {
+                tracing::info!(
+                    "Creating index {USER_ID_INDEX} on DynamoDB table {}",
+                    self.1
+                );
+                self.create_index(&table).await?;
+                self.wait_until_active().await
+            }
+        }
+    }
+
+    fn schema_error(&self, reason: &str) -> BootstrapError {
+        BootstrapError::Schema {
+            table: self.1.clone(),
+            reason: String::from(reason),
+        }
+    }
+
+    async fn describe(&self) -> Result<Option<TableDescription>, BootstrapError> {
+        match self.0.describe_table().table_name(&self.1).send().await {
+            Ok(output) => Ok(output.table),
+            Err(SdkError::ServiceError(why)) if why.err().is_resource_not_found_exception() => {
+                Ok(None)
+            }
+            Err(why) => Err(BootstrapError::request(why)),
+        }
+    }
+
+    async fn create_table(&self) -> Result<(), BootstrapError> {
+        let (index_keys, projection) = user_id_index()?;
+        let index = GlobalSecondaryIndex::builder()
+            .index_name(USER_ID_INDEX)
+            .set_key_schema(Some(index_keys))
+            .projection(projection)
+            .build()
+            .map_err(BootstrapError::request)?;
+
+        self.0
+            .create_table()
+            .table_name(&self.1)
+            .attribute_definitions(string_attribute("bot_id")?)
+            .attribute_definitions(string_attribute("user_id")?)
+            .key_schema(key("bot_id", KeyType::Hash)?)
+            .key_schema(key("user_id", KeyType::Range)?)
+            .global_secondary_indexes(index)
+            .billing_mode(BillingMode::PayPerRequest)
+            .send()
+            .await
+            .map_err(BootstrapError::request)?;
+
+        Ok(())
+    }
+
+    async fn create_index(&self, table: &TableDescription) -> Result<(), BootstrapError> {
+        let (index_keys, projection) = user_id_index()?;
+        // Indexes on provisioned tables need their own capacity, so reuse the table's.
+        let throughput = match table.billing_mode_summary().and_then(|b| b.billing_mode()) {
+            Some(BillingMode::PayPerRequest) => None,
+            _ => table.provisioned_throughput().and_then(|throughput| {
+                ProvisionedThroughput::builder()
+                    .set_read_capacity_units(throughput.read_capacity_units())
+                    .set_write_capacity_units(throughput.write_capacity_units())
+                    .build()
+                    .ok()
+            }),
+        };
+
+        let index = CreateGlobalSecondaryIndexAction::builder()
+            .index_name(USER_ID_INDEX)
+            .set_key_schema(Some(index_keys))
+            .projection(projection)
+            .set_provisioned_throughput(throughput)
+            .build()
+            .map_err(BootstrapError::request)?;
+
+        self.0
+            .update_table()
+            .table_name(&self.1)
+            .attribute_definitions(string_attribute("bot_id")?)
+            .attribute_definitions(string_attribute("user_id")?)
+            .global_secondary_index_updates(
+                GlobalSecondaryIndexUpdate::builder().create(index).build(),
+            )
+            .send()
+            .await
+            .map_err(BootstrapError::request)?;
+
+        Ok(())
+    }
+
+    async fn wait_until_active(&self) -> Result<(), BootstrapError> {
+        let deadline = tokio::time::Instant::now() + BOOTSTRAP_TIMEOUT;
+
+        loop {
+            if self.describe().await?.as_ref().is_some_and(is_active) {
+                return Ok(());
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(BootstrapError::Timeout(self.1.clone()));
+            }
+
+            tokio::time::sleep(BOOTSTRAP_POLL_INTERVAL).await;
+        }
     }
 }
 
 #[async_trait]
 impl Register for DynamoDB {
-    async fn fetch(&self, bot_id: String) -> Option<Vec<RegisterEntry>> {
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
         let bot_id_attr_value = AttributeValue::S(bot_id.clone());
         let query_op = self
             .0
@@ -26,32 +318,33 @@ impl Register for DynamoDB {
             .table_name(&self.1)
             .key_condition_expression("bot_id = :value")
             .expression_attribute_values(":value", bot_id_attr_value)
+            .into_paginator()
+            .items()
             .send()
+            .try_collect()
             .await;
 
         let results = match query_op {
             Err(e) => {
-                log::error!("{e:?}");
-                return None;
+                tracing::error!("{e:?}");
+                return Err(RegisterError::EntryFetchError(storage_error(e)));
             }
-            Ok(value) => value.items?,
+            Ok(items) => items,
         };
 
-        Some(
-            results
-                .into_iter()
-                .filter_map(|value| {
-                    let AttributeValue::S(user_id) = value.get("user_id")? else {
-                        return None;
-                    };
-
-                    Some(RegisterEntry {
-                        user_id: user_id.clone(),
-                        bot_id: bot_id.clone(),
-                    })
+        Ok(results
+            .into_iter()
+            .filter_map(|value| {
+                let AttributeValue::S(user_id) = value.get("user_id")? else {
+                    return None;
+                };
+
+                Some(RegisterEntry {
+                    user_id: user_id.clone(),
+                    bot_id: bot_id.clone(),
                 })
-                .collect(),
-        )
+            })
+            .collect())
     }
 
     async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
@@ -59,20 +352,62 @@ impl Register for DynamoDB {
         let bot_id = AttributeValue::S(entry.bot_id);
         let entry_version = AttributeValue::S(entry.version.to_string());
 
-        let request = self
+        let query_op = self
             .0
             .put_item()
             .table_name(&self.1)
             .item("bot_id", bot_id)
             .item("user_id", user_id)
-            .item("entry_version", entry_version);
+            .item("entry_version", entry_version)
+            .condition_expression("attribute_not_exists(bot_id)")
+            .send()
+            .await;
 
-        if let Err(why) = request.send().await {
-            log::error!("failed to send add request: {why:?}");
-            return Err(RegisterError::EntryCreationError);
+        match query_op {
+            Err(SdkError::ServiceError(why))
+                if why.err().is_conditional_check_failed_exception() =>
+            {
+                Err(RegisterError::AlreadyRegistered)
+            }
+            Err(why) => {
+                tracing::error!("failed to send add request: {why:?}");
+                Err(RegisterError::EntryCreationError(storage_error(why)))
+            }
+            Ok(_) => Ok(()),
         }
+    }
 
-        Ok(())
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let user_id = AttributeValue::S(entry.user_id);
+        let bot_id = AttributeValue::S(entry.bot_id);
+        let entry_version = AttributeValue::S(entry.version.to_string());
+
+        // Only set what an update changes, so attributes written by anything
+        // else are left alone.
+        let query_op = self
+            .0
+            .update_item()
+            .table_name(&self.1)
+            .key("bot_id", bot_id)
+            .key("user_id", user_id)
+            .update_expression("SET entry_version = :version")
+            .expression_attribute_values(":version", entry_version)
+            .condition_expression("attribute_exists(bot_id)")
+            .send()
+            .await;
+
+        match query_op {
+            Err(SdkError::ServiceError(why))
+                if why.err().is_conditional_check_failed_exception() =>
+            {
+                Err(RegisterError::NotRegistered)
+            }
+            Err(why) => {
+                tracing::error!("failed to send update request: {why:?}");
+                Err(RegisterError::EntryUpdateError(storage_error(why)))
+            }
+            Ok(_) => Ok(()),
+        }
     }
 
     async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
@@ -84,17 +419,23 @@ impl Register for DynamoDB {
             .delete_item()
             .table_name(&self.1)
             .key("bot_id", bot_id_attr_value)
-            .condition_expression("user_id = :value")
-            .expression_attribute_values(":value", user_id_attr_value)
+            .key("user_id", user_id_attr_value)
+            .condition_expression("attribute_exists(bot_id)")
             .send()
             .await;
 
-        if let Err(why) = query_op {
-            log::error!("failed to send delete request: {why:?}");
-            return Err(RegisterError::EntryRemoveError);
+        match query_op {
+            Err(SdkError::ServiceError(why))
+                if why.err().is_conditional_check_failed_exception() =>
+            {
+                Err(RegisterError::NotRegistered)
+            }
+            Err(why) => {
+                tracing::error!("failed to send delete request: {why:?}");
+                Err(RegisterError::EntryRemoveError(storage_error(why)))
+            }
+            Ok(_) => Ok(()),
         }
-
-        Ok(())
     }
 
     async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
@@ -103,18 +444,21 @@ impl Register for DynamoDB {
             .0
             .query()
             .table_name(&self.1)
-            .index_name("user_id-index")
+            .index_name(USER_ID_INDEX)
             .key_condition_expression("user_id = :value")
             .expression_attribute_values(":value", user_id_attr_value)
+            .into_paginator()
+            .items()
             .send()
+            .try_collect()
             .await;
 
         let results = match query_op {
             Err(e) => {
-                log::error!("{e:?}");
-                return Err(RegisterError::EntryFetchError);
+                tracing::error!("{e:?}");
+                return Err(RegisterError::EntryFetchError(storage_error(e)));
             }
-            Ok(value) => value.items.unwrap(),
+            Ok(items) => items,
         };
 
         Ok(results
@@ -131,18 +475,139 @@ impl Register for DynamoDB {
             })
             .collect())
     }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        let payload = ListEntriesPayload {
+            user_id: user_id.clone(),
+        };
+        let entries = match self.list(payload).await {
+            Err(RegisterError::EntryFetchError(why)) => {
+                return Err(RegisterError::EntryRemoveError(why));
+            }
+            result => result?,
+        };
+
+        let mut forgotten = 0;
+        for entry in entries {
+            let query_op = self
+                .0
+                .delete_item()
+                .table_name(&self.1)
+                .key("bot_id", AttributeValue::S(entry.bot_id))
+                .key("user_id", AttributeValue::S(user_id.clone()))
+                .send()
+                .await;
+
+            if let Err(why) = query_op {
+                tracing::error!("failed to send forget request after {forgotten} entries: {why:?}");
+                return Err(RegisterError::EntryRemoveError(storage_error(why)));
+            }
+            forgotten += 1;
+        }
+
+        Ok(forgotten)
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        self.scan_distinct("bot_id").await
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        self.scan_distinct("user_id").await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::adapters::register::testing::unique_id;
     use aws_sdk_dynamodb::Client;
+    use aws_sdk_dynamodb::config::{BehaviorVersion, Credentials, Region};
     use aws_sdk_dynamodb::error::ErrorMetadata;
+    use aws_sdk_dynamodb::operation::create_table::CreateTableOutput;
     use aws_sdk_dynamodb::operation::delete_item::{DeleteItemError, DeleteItemOutput};
+    use aws_sdk_dynamodb::operation::describe_table::{DescribeTableError, DescribeTableOutput};
     use aws_sdk_dynamodb::operation::put_item::{PutItemError, PutItemOutput};
     use aws_sdk_dynamodb::operation::query::{QueryError, QueryOutput};
-    use aws_smithy_mocks::{mock, mock_client};
+    use aws_sdk_dynamodb::operation::scan::{ScanError, ScanOutput};
+    use aws_sdk_dynamodb::operation::update_item::{UpdateItemError, UpdateItemOutput};
+    use aws_sdk_dynamodb::operation::update_table::UpdateTableOutput;
+    use aws_sdk_dynamodb::types::GlobalSecondaryIndexDescription;
+    use aws_sdk_dynamodb::types::error::{
+        ConditionalCheckFailedException, ResourceNotFoundException,
+    };
+    use aws_smithy_mocks::{RuleMode, mock, mock_client};
     use std::collections::HashMap;
+    use std::env;
+    use std::ops::Deref;
+    use std::thread;
+
+    fn local_client() -> Client {
+        let endpoint_url =
+            env::var("DYNAMODB_TEST_ENDPOINT").expect("DYNAMODB_TEST_ENDPOINT is not set");
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("local", "local", None, None, "test"))
+            .endpoint_url(endpoint_url)
+            .build();
+
+        Client::from_conf(config)
+    }
+
+    /// A register on its own table in DynamoDB Local, deleted again when the
+    /// test ends, whether it passed or not.
+    struct LocalRegister(DynamoDB);
+
+    impl Deref for LocalRegister {
+        type Target = DynamoDB;
+
+        fn deref(&self) -> &DynamoDB {
+            &self.0
+        }
+    }
+
+    impl Drop for LocalRegister {
+        fn drop(&mut self) {
+            // Drop can't await, and the test's runtime is blocked on this
+            // thread, so delete it from another one with a client of its own.
+            let table_name = self.0.1.clone();
+            let deleted = thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+                runtime
+                    .block_on(local_client().delete_table().table_name(table_name).send())
+                    .is_ok()
+            })
+            .join();
+
+            if !matches!(deleted, Ok(true)) {
+                eprintln!("Could not delete DynamoDB Local table {}", self.0.1);
+            }
+        }
+    }
+
+    async fn local_register() -> LocalRegister {
+        let register = LocalRegister(DynamoDB(local_client(), unique_id("register")));
+        register.bootstrap().await.unwrap();
+        register
+    }
+
+    /// Writes an entry big enough that a few of them spill over DynamoDB's 1MB query page.
+    async fn put_large_entry(register: &DynamoDB, bot_id: &str, user_id: &str) {
+        register
+            .0
+            .put_item()
+            .table_name(&register.1)
+            .item("bot_id", AttributeValue::S(String::from(bot_id)))
+            .item("user_id", AttributeValue::S(String::from(user_id)))
+            .item("padding", AttributeValue::S("x".repeat(300_000)))
+            .send()
+            .await
+            .unwrap();
+    }
 
     #[tokio::test]
     async fn test_list_error() {
@@ -173,7 +638,7 @@ mod tests {
 
         let return_value = dynamo_register.list(payload).await.unwrap_err();
         assert_eq!(query.num_calls(), 1);
-        assert_eq!(return_value, RegisterError::EntryFetchError)
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)))
     }
 
     #[tokio::test]
@@ -245,6 +710,118 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_list_pages() {
+        let user_id = String::from("user_id_12345");
+        let user_id_clone = user_id.clone();
+        let table_name = String::from("table_name");
+        let payload = ListEntriesPayload {
+            user_id: user_id.clone(),
+        };
+        let last_key = HashMap::from([
+            (String::from("user_id"), AttributeValue::S(user_id.clone())),
+            (
+                String::from("bot_id"),
+                AttributeValue::S(String::from("bot_id_0")),
+            ),
+        ]);
+        let last_key_clone = last_key.clone();
+
+        let first_page = mock!(Client::query)
+            .match_requests(|req| req.exclusive_start_key.is_none())
+            .then_output(move || {
+                QueryOutput::builder()
+                    .count(1)
+                    .items(last_key.clone())
+                    .set_last_evaluated_key(Some(last_key.clone()))
+                    .build()
+            });
+        let second_page = mock!(Client::query)
+            .match_requests(move |req| req.exclusive_start_key == Some(last_key_clone.clone()))
+            .then_output(move || {
+                QueryOutput::builder()
+                    .count(1)
+                    .items(HashMap::from([
+                        (String::from("user_id"), AttributeValue::S(user_id.clone())),
+                        (
+                            String::from("bot_id"),
+                            AttributeValue::S(String::from("bot_id_1")),
+                        ),
+                    ]))
+                    .build()
+            });
+
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::Sequential,
+            [&first_page, &second_page]
+        );
+
+        let dynamo_register = DynamoDB(dynamodb_client, table_name);
+
+        let return_value = dynamo_register.list(payload).await.unwrap();
+        assert_eq!(first_page.num_calls(), 1);
+        assert_eq!(second_page.num_calls(), 1);
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.user_id, user_id_clone.clone());
+            assert_eq!(entry.bot_id, format!("bot_id_{}", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_no_items() {
+        let payload = ListEntriesPayload {
+            user_id: String::from("user_id_12345"),
+        };
+
+        let query = mock!(Client::query).then_output(|| QueryOutput::builder().count(0).build());
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&query]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("table_name"));
+
+        let return_value = dynamo_register.list(payload).await.unwrap();
+        assert_eq!(query.num_calls(), 1);
+        assert!(return_value.is_empty())
+    }
+
+    #[tokio::test]
+    async fn test_list_second_page_error() {
+        let payload = ListEntriesPayload {
+            user_id: String::from("user_id_12345"),
+        };
+        let last_key = HashMap::from([(
+            String::from("bot_id"),
+            AttributeValue::S(String::from("bot_id_0")),
+        )]);
+
+        let first_page = mock!(Client::query)
+            .match_requests(|req| req.exclusive_start_key.is_none())
+            .then_output(move || {
+                QueryOutput::builder()
+                    .count(1)
+                    .items(last_key.clone())
+                    .set_last_evaluated_key(Some(last_key.clone()))
+                    .build()
+            });
+        let second_page = mock!(Client::query)
+            .match_requests(|req| req.exclusive_start_key.is_some())
+            .then_error(|| QueryError::generic(ErrorMetadata::default()));
+
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::Sequential,
+            [&first_page, &second_page]
+        );
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("table_name"));
+
+        let return_value = dynamo_register.list(payload).await.unwrap_err();
+        assert_eq!(second_page.num_calls(), 1);
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)))
+    }
+
     #[tokio::test]
     async fn test_remove_error() {
         let bot_id = String::from("bot_id_12345");
@@ -259,16 +836,11 @@ mod tests {
             .match_requests(move |req| {
                 req.table_name == Some(String::from("test-register"))
                     && req.key
-                        == Some(HashMap::from([(
-                            String::from("bot_id"),
-                            AttributeValue::S(bot_id.clone()),
-                        )]))
-                    && req.condition_expression == Some(String::from("user_id = :value"))
-                    && req.expression_attribute_values
-                        == Some(HashMap::from([(
-                            String::from(":value"),
-                            AttributeValue::S(user_id.clone()),
-                        )]))
+                        == Some(HashMap::from([
+                            (String::from("bot_id"), AttributeValue::S(bot_id.clone())),
+                            (String::from("user_id"), AttributeValue::S(user_id.clone())),
+                        ]))
+                    && req.condition_expression == Some(String::from("attribute_exists(bot_id)"))
             })
             .then_error(|| DeleteItemError::generic(ErrorMetadata::builder().build()));
 
@@ -278,7 +850,29 @@ mod tests {
 
         let return_value = dynamo_register.remove(entry).await.unwrap_err();
         assert_eq!(delete_item.num_calls(), 1);
-        assert_eq!(return_value, RegisterError::EntryRemoveError)
+        assert!(matches!(return_value, RegisterError::EntryRemoveError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_remove_not_registered() {
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        let delete_item = mock!(Client::delete_item).then_error(|| {
+            DeleteItemError::ConditionalCheckFailedException(
+                ConditionalCheckFailedException::builder().build(),
+            )
+        });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&delete_item]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.remove(entry).await.unwrap_err();
+        assert_eq!(delete_item.num_calls(), 1);
+        assert_eq!(return_value, RegisterError::NotRegistered)
     }
 
     #[tokio::test]
@@ -295,16 +889,11 @@ mod tests {
             .match_requests(move |req| {
                 req.table_name == Some(String::from("test-register"))
                     && req.key
-                        == Some(HashMap::from([(
-                            String::from("bot_id"),
-                            AttributeValue::S(bot_id.clone()),
-                        )]))
-                    && req.condition_expression == Some(String::from("user_id = :value"))
-                    && req.expression_attribute_values
-                        == Some(HashMap::from([(
-                            String::from(":value"),
-                            AttributeValue::S(user_id.clone()),
-                        )]))
+                        == Some(HashMap::from([
+                            (String::from("bot_id"), AttributeValue::S(bot_id.clone())),
+                            (String::from("user_id"), AttributeValue::S(user_id.clone())),
+                        ]))
+                    && req.condition_expression == Some(String::from("attribute_exists(bot_id)"))
             })
             .then_output(|| DeleteItemOutput::builder().build());
 
@@ -312,13 +901,12 @@ mod tests {
 
         let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
 
-        let return_value = dynamo_register.remove(entry).await.unwrap();
+        dynamo_register.remove(entry).await.unwrap();
         assert_eq!(delete_item.num_calls(), 1);
-        assert_eq!(return_value, ())
     }
 
     #[tokio::test]
-    async fn test_fetch_none_return() {
+    async fn test_fetch_error() {
         let bot_id = String::from("bot_id_12345");
         let bot_id_clone = bot_id.clone();
         let table_name = String::from("table_name");
@@ -340,9 +928,32 @@ mod tests {
 
         let dynamo_register = DynamoDB(dynamodb_client, table_name.clone());
 
-        let return_value = dynamo_register.fetch(bot_id.clone()).await;
+        let return_value = dynamo_register.fetch(bot_id.clone()).await.unwrap_err();
         assert_eq!(query.num_calls(), 1);
-        assert!(return_value.is_none())
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)));
+        assert!(!return_value.is_retryable())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_throttled() {
+        let query = mock!(Client::query).then_error(|| {
+            QueryError::generic(
+                ErrorMetadata::builder()
+                    .code("ProvisionedThroughputExceededException")
+                    .build(),
+            )
+        });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&query]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("table_name"));
+
+        let return_value = dynamo_register
+            .fetch(String::from("bot_id_12345"))
+            .await
+            .unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)));
+        assert!(return_value.is_retryable())
     }
 
     #[tokio::test]
@@ -403,6 +1014,60 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_fetch_pages() {
+        let bot_id = String::from("bot_id_12345");
+        let bot_id_clone = bot_id.clone();
+        let table_name = String::from("table_name");
+
+        let pages: Vec<_> = (0..3)
+            .map(|page| {
+                let bot_id = bot_id.clone();
+                let start_key = (page > 0).then(|| {
+                    HashMap::from([
+                        (String::from("bot_id"), AttributeValue::S(bot_id.clone())),
+                        (
+                            String::from("user_id"),
+                            AttributeValue::S(format!("user_id_{}", page - 1)),
+                        ),
+                    ])
+                });
+                let item = HashMap::from([
+                    (String::from("bot_id"), AttributeValue::S(bot_id.clone())),
+                    (
+                        String::from("user_id"),
+                        AttributeValue::S(format!("user_id_{page}")),
+                    ),
+                ]);
+                let last_key = (page < 2).then(|| item.clone());
+
+                mock!(Client::query)
+                    .match_requests(move |req| req.exclusive_start_key == start_key)
+                    .then_output(move || {
+                        QueryOutput::builder()
+                            .count(1)
+                            .items(item.clone())
+                            .set_last_evaluated_key(last_key.clone())
+                            .build()
+                    })
+            })
+            .collect();
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, RuleMode::Sequential, pages.iter());
+
+        let dynamo_register = DynamoDB(dynamodb_client, table_name);
+
+        let return_value = dynamo_register.fetch(bot_id_clone.clone()).await.unwrap();
+        for page in &pages {
+            assert_eq!(page.num_calls(), 1);
+        }
+        assert_eq!(return_value.len(), 3);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.bot_id, bot_id_clone.clone());
+            assert_eq!(entry.user_id, format!("user_id_{}", i));
+        }
+    }
+
     #[tokio::test]
     async fn test_add() {
         let bot_id = String::from("bot_id_12345");
@@ -412,7 +1077,7 @@ mod tests {
         let entry = CreateEntry {
             user_id: user_id.clone(),
             bot_id: bot_id.clone(),
-            version: version.clone(),
+            version,
         };
 
         let put_object = mock!(Client::put_item)
@@ -427,6 +1092,8 @@ mod tests {
                                 AttributeValue::S(version.to_string()),
                             ),
                         ]))
+                    && req.condition_expression
+                        == Some(String::from("attribute_not_exists(bot_id)"))
             })
             .then_output(|| {
                 PutItemOutput::builder()
@@ -441,9 +1108,8 @@ mod tests {
 
         let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
 
-        let return_value = dynamo_register.add(entry).await.unwrap();
+        dynamo_register.add(entry).await.unwrap();
         assert_eq!(put_object.num_calls(), 1);
-        assert_eq!(return_value, ())
     }
 
     #[tokio::test]
@@ -455,7 +1121,7 @@ mod tests {
         let entry = CreateEntry {
             user_id: user_id.clone(),
             bot_id: bot_id.clone(),
-            version: version.clone(),
+            version,
         };
 
         let put_object = mock!(Client::put_item)
@@ -470,6 +1136,8 @@ mod tests {
                                 AttributeValue::S(version.to_string()),
                             ),
                         ]))
+                    && req.condition_expression
+                        == Some(String::from("attribute_not_exists(bot_id)"))
             })
             .then_error(|| PutItemError::generic(ErrorMetadata::builder().build()));
 
@@ -479,6 +1147,474 @@ mod tests {
 
         let return_value = dynamo_register.add(entry).await.unwrap_err();
         assert_eq!(put_object.num_calls(), 1);
-        assert_eq!(return_value, RegisterError::EntryCreationError)
+        assert!(matches!(return_value, RegisterError::EntryCreationError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_add_already_registered() {
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 0,
+        };
+
+        let put_object = mock!(Client::put_item).then_error(|| {
+            PutItemError::ConditionalCheckFailedException(
+                ConditionalCheckFailedException::builder().build(),
+            )
+        });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&put_object]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.add(entry).await.unwrap_err();
+        assert_eq!(put_object.num_calls(), 1);
+        assert_eq!(return_value, RegisterError::AlreadyRegistered)
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let bot_id = String::from("bot_id_12345");
+        let user_id = String::from("user_id_12345");
+        let version = 1;
+
+        let entry = CreateEntry {
+            user_id: user_id.clone(),
+            bot_id: bot_id.clone(),
+            version,
+        };
+
+        let update_object = mock!(Client::update_item)
+            .match_requests(move |req| {
+                req.table_name == Some(String::from("test-register"))
+                    && req.key
+                        == Some(HashMap::from([
+                            (String::from("bot_id"), AttributeValue::S(bot_id.clone())),
+                            (String::from("user_id"), AttributeValue::S(user_id.clone())),
+                        ]))
+                    && req.update_expression == Some(String::from("SET entry_version = :version"))
+                    && req.expression_attribute_values
+                        == Some(HashMap::from([(
+                            String::from(":version"),
+                            AttributeValue::S(version.to_string()),
+                        )]))
+                    && req.condition_expression == Some(String::from("attribute_exists(bot_id)"))
+            })
+            .then_output(|| UpdateItemOutput::builder().build());
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&update_object]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        dynamo_register.update(entry).await.unwrap();
+        assert_eq!(update_object.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_not_registered() {
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        let update_object = mock!(Client::update_item).then_error(|| {
+            UpdateItemError::ConditionalCheckFailedException(
+                ConditionalCheckFailedException::builder().build(),
+            )
+        });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&update_object]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.update(entry).await.unwrap_err();
+        assert_eq!(update_object.num_calls(), 1);
+        assert_eq!(return_value, RegisterError::NotRegistered)
+    }
+
+    #[tokio::test]
+    async fn test_update_error() {
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        let update_object = mock!(Client::update_item)
+            .then_error(|| UpdateItemError::generic(ErrorMetadata::builder().build()));
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&update_object]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.update(entry).await.unwrap_err();
+        assert_eq!(update_object.num_calls(), 1);
+        assert!(matches!(return_value, RegisterError::EntryUpdateError(_)))
+    }
+
+    fn table(key_schema: Vec<KeySchemaElement>, with_index: bool) -> DescribeTableOutput {
+        let index = GlobalSecondaryIndexDescription::builder()
+            .index_name(USER_ID_INDEX)
+            .key_schema(key("user_id", KeyType::Hash).unwrap())
+            .key_schema(key("bot_id", KeyType::Range).unwrap())
+            .index_status(IndexStatus::Active)
+            .build();
+
+        DescribeTableOutput::builder()
+            .table(
+                TableDescription::builder()
+                    .table_name("test-register")
+                    .set_key_schema(Some(key_schema))
+                    .table_status(TableStatus::Active)
+                    .set_global_secondary_indexes(with_index.then(|| vec![index]))
+                    .build(),
+            )
+            .build()
+    }
+
+    fn register_keys() -> Vec<KeySchemaElement> {
+        vec![
+            key("bot_id", KeyType::Hash).unwrap(),
+            key("user_id", KeyType::Range).unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_without_retries() {
+        let register = DynamoDB::new(String::from("test-register"), None)
+            .await
+            .without_retries();
+
+        let retry_config = register.0.config().retry_config().unwrap();
+        assert_eq!(retry_config.max_attempts(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_existing_table() {
+        let describe_table =
+            mock!(Client::describe_table).then_output(|| table(register_keys(), true));
+        let create_table =
+            mock!(Client::create_table).then_output(|| CreateTableOutput::builder().build());
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&describe_table, &create_table]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        dynamo_register.bootstrap().await.unwrap();
+        assert_eq!(create_table.num_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_creates_table() {
+        let missing = mock!(Client::describe_table).then_error(|| {
+            DescribeTableError::ResourceNotFoundException(
+                ResourceNotFoundException::builder().build(),
+            )
+        });
+        let create_table = mock!(Client::create_table)
+            .match_requests(|req| {
+                req.table_name() == Some("test-register")
+                    && req.key_schema() == register_keys()
+                    && req.global_secondary_indexes()[0].index_name() == USER_ID_INDEX
+            })
+            .then_output(|| CreateTableOutput::builder().build());
+        let created = mock!(Client::describe_table).then_output(|| table(register_keys(), true));
+
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::Sequential,
+            [&missing, &create_table, &created]
+        );
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        dynamo_register.bootstrap().await.unwrap();
+        assert_eq!(create_table.num_calls(), 1);
+        assert_eq!(created.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_creates_index() {
+        let without_index =
+            mock!(Client::describe_table).then_output(|| table(register_keys(), false));
+        let update_table = mock!(Client::update_table)
+            .match_requests(|req| {
+                req.global_secondary_index_updates()[0]
+                    .create()
+                    .is_some_and(|index| index.index_name() == USER_ID_INDEX)
+            })
+            .then_output(|| UpdateTableOutput::builder().build());
+        let with_index = mock!(Client::describe_table).then_output(|| table(register_keys(), true));
+
+        let dynamodb_client = mock_client!(
+            aws_sdk_dynamodb,
+            RuleMode::Sequential,
+            [&without_index, &update_table, &with_index]
+        );
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        dynamo_register.bootstrap().await.unwrap();
+        assert_eq!(update_table.num_calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_wrong_schema() {
+        let describe_table = mock!(Client::describe_table)
+            .then_output(|| table(vec![key("user_id", KeyType::Hash).unwrap()], true));
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&describe_table]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.bootstrap().await.unwrap_err();
+        assert!(matches!(return_value, BootstrapError::Schema { .. }))
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_add_and_fetch() {
+        let register = local_register().await;
+        let bot_id = unique_id("bot_id");
+        for i in 0..2 {
+            let entry = CreateEntry {
+                user_id: format!("{bot_id}_user_id_{i}"),
+                bot_id: bot_id.clone(),
+                version: 0,
+            };
+            register.add(entry.clone()).await.unwrap();
+            let return_value = register.add(entry).await.unwrap_err();
+            assert_eq!(return_value, RegisterError::AlreadyRegistered);
+        }
+
+        let return_value = register.fetch(bot_id.clone()).await.unwrap();
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.bot_id, bot_id.clone());
+            assert_eq!(entry.user_id, format!("{bot_id}_user_id_{i}"));
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_add_and_list() {
+        let register = local_register().await;
+        let user_id = unique_id("user_id");
+        for i in 0..2 {
+            let entry = CreateEntry {
+                user_id: user_id.clone(),
+                bot_id: format!("{user_id}_bot_id_{i}"),
+                version: 0,
+            };
+            register.add(entry).await.unwrap();
+        }
+        let payload = ListEntriesPayload {
+            user_id: user_id.clone(),
+        };
+
+        let return_value = register.list(payload).await.unwrap();
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.user_id, user_id.clone());
+            assert_eq!(entry.bot_id, format!("{user_id}_bot_id_{i}"));
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_update() {
+        let register = local_register().await;
+        let entry = CreateEntry {
+            user_id: unique_id("user_id"),
+            bot_id: unique_id("bot_id"),
+            version: 0,
+        };
+
+        let return_value = register.update(entry.clone()).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered);
+
+        register.add(entry.clone()).await.unwrap();
+        register.update(entry).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_remove() {
+        let register = local_register().await;
+        let bot_id = unique_id("bot_id");
+        let user_id = unique_id("user_id");
+        for other_user_id in [&user_id, &unique_id("user_id")] {
+            let entry = CreateEntry {
+                user_id: other_user_id.clone(),
+                bot_id: bot_id.clone(),
+                version: 0,
+            };
+            register.add(entry).await.unwrap();
+        }
+        let entry = RemoveEntry {
+            user_id: user_id.clone(),
+            bot_id,
+        };
+
+        register.remove(entry.clone()).await.unwrap();
+        let return_value = register.remove(entry.clone()).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered);
+
+        let return_value = register.fetch(entry.bot_id).await.unwrap();
+        assert_eq!(return_value.len(), 1);
+        assert_ne!(return_value[0].user_id, user_id);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_pages() {
+        let register = local_register().await;
+        let bot_id = unique_id("bot_id");
+        let user_id = unique_id("user_id");
+        for i in 0..5 {
+            put_large_entry(&register, &bot_id, &format!("{user_id}_{i}")).await;
+            put_large_entry(&register, &format!("{bot_id}_{i}"), &user_id).await;
+        }
+
+        let return_value = register.fetch(bot_id).await.unwrap();
+        assert_eq!(return_value.len(), 5);
+
+        let payload = ListEntriesPayload { user_id };
+        let return_value = register.list(payload).await.unwrap();
+        assert_eq!(return_value.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_forget_user() {
+        let query = mock!(Client::query)
+            .match_requests(|req| req.index_name() == Some(USER_ID_INDEX))
+            .then_output(|| {
+                QueryOutput::builder()
+                    .count(2)
+                    .items(HashMap::from([(
+                        String::from("bot_id"),
+                        AttributeValue::S(String::from("bot_id_0")),
+                    )]))
+                    .items(HashMap::from([(
+                        String::from("bot_id"),
+                        AttributeValue::S(String::from("bot_id_1")),
+                    )]))
+                    .build()
+            });
+        let delete_item = mock!(Client::delete_item)
+            .match_requests(|req| {
+                req.key().is_some_and(|key| {
+                    key.get("user_id") == Some(&AttributeValue::S(String::from("user_id_12345")))
+                        && key.contains_key("bot_id")
+                })
+            })
+            .sequence()
+            .output(|| DeleteItemOutput::builder().build())
+            .times(2)
+            .build();
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&query, &delete_item]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap();
+        assert_eq!(return_value, 2);
+        assert_eq!(delete_item.num_calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_monitored_bots() {
+        let scan = mock!(Client::scan)
+            .match_requests(|req| req.projection_expression() == Some("bot_id"))
+            .then_output(|| {
+                ScanOutput::builder()
+                    .count(3)
+                    .items(HashMap::from([(
+                        String::from("bot_id"),
+                        AttributeValue::S(String::from("bot_id_0")),
+                    )]))
+                    .items(HashMap::from([(
+                        String::from("bot_id"),
+                        AttributeValue::S(String::from("bot_id_1")),
+                    )]))
+                    .items(HashMap::from([(
+                        String::from("bot_id"),
+                        AttributeValue::S(String::from("bot_id_1")),
+                    )]))
+                    .build()
+            });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&scan]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.monitored_bots().await.unwrap();
+        assert_eq!(
+            return_value,
+            HashSet::from([String::from("bot_id_0"), String::from("bot_id_1")])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registrants_error() {
+        let scan = mock!(Client::scan)
+            .match_requests(|req| req.projection_expression() == Some("user_id"))
+            .then_error(|| {
+                ScanError::generic(
+                    ErrorMetadata::builder()
+                        .code("AccessDeniedException")
+                        .build(),
+                )
+            });
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&scan]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register.registrants().await.unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)));
+        assert!(!return_value.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_forget_user_error() {
+        let query =
+            mock!(Client::query).then_error(|| QueryError::generic(ErrorMetadata::default()));
+
+        let dynamodb_client = mock_client!(aws_sdk_dynamodb, [&query]);
+
+        let dynamo_register = DynamoDB(dynamodb_client, String::from("test-register"));
+
+        let return_value = dynamo_register
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryRemoveError(_)))
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a DynamoDB Local endpoint"]
+    async fn test_local_forget_user() {
+        let register = local_register().await;
+        let user_id = unique_id("user_id");
+        for i in 0..2 {
+            let entry = CreateEntry {
+                user_id: user_id.clone(),
+                bot_id: format!("{user_id}_bot_id_{i}"),
+                version: 0,
+            };
+            register.add(entry).await.unwrap();
+        }
+
+        let return_value = register.forget_user(user_id.clone()).await.unwrap();
+        assert_eq!(return_value, 2);
+
+        let payload = ListEntriesPayload { user_id };
+        assert!(register.list(payload).await.unwrap().is_empty());
     }
 }
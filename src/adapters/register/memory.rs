@@ -0,0 +1,356 @@
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError, StorageError};
+use async_trait::async_trait;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::RwLock;
+
+/// Keeps the register in process memory, keyed by bot id and then user id.
+/// Nothing is persisted, so this is only meant for local development.
+#[derive(Default)]
+pub struct InMemory(RwLock<HashMap<String, BTreeSet<String>>>);
+
+impl InMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn poisoned() -> StorageError {
+    tracing::error!("in memory register lock was poisoned");
+    StorageError::permanent("in memory register lock was poisoned")
+}
+
+#[async_trait]
+impl Register for InMemory {
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        let Ok(register) = self.0.read() else {
+            return Err(RegisterError::EntryFetchError(poisoned()));
+        };
+
+        Ok(register
+            .get(&bot_id)
+            .into_iter()
+            .flatten()
+            .map(|user_id| RegisterEntry {
+                bot_id: bot_id.clone(),
+                user_id: user_id.clone(),
+            })
+            .collect())
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let Ok(mut register) = self.0.write() else {
+            return Err(RegisterError::EntryCreationError(poisoned()));
+        };
+
+        if register
+            .entry(entry.bot_id)
+            .or_default()
+            .insert(entry.user_id)
+        {
+            Ok(())
+        } else {
+            Err(RegisterError::AlreadyRegistered)
+        }
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let Ok(register) = self.0.read() else {
+            return Err(RegisterError::EntryUpdateError(poisoned()));
+        };
+
+        // Entries carry nothing besides their key yet, so there is nothing to change.
+        if register
+            .get(&entry.bot_id)
+            .is_some_and(|users| users.contains(&entry.user_id))
+        {
+            Ok(())
+        } else {
+            Err(RegisterError::NotRegistered)
+        }
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        let Ok(mut register) = self.0.write() else {
+            return Err(RegisterError::EntryRemoveError(poisoned()));
+        };
+
+        let Some(users) = register.get_mut(&entry.bot_id) else {
+            return Err(RegisterError::NotRegistered);
+        };
+
+        if !users.remove(&entry.user_id) {
+            return Err(RegisterError::NotRegistered);
+        }
+
+        if users.is_empty() {
+            register.remove(&entry.bot_id);
+        }
+
+        Ok(())
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        let Ok(register) = self.0.read() else {
+            return Err(RegisterError::EntryFetchError(poisoned()));
+        };
+
+        Ok(register
+            .iter()
+            .filter(|(_, users)| users.contains(&entry.user_id))
+            .map(|(bot_id, _)| RegisterEntry {
+                bot_id: bot_id.clone(),
+                user_id: entry.user_id.clone(),
+            })
+            .collect())
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        let Ok(mut register) = self.0.write() else {
+            return Err(RegisterError::EntryRemoveError(poisoned()));
+        };
+
+        let mut forgotten = 0;
+        register.retain(|_, users| {
+            if users.remove(&user_id) {
+                forgotten += 1;
+            }
+            !users.is_empty()
+        });
+
+        Ok(forgotten)
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        let Ok(register) = self.0.read() else {
+            return Err(RegisterError::EntryFetchError(poisoned()));
+        };
+
+        // Bots are dropped once their last user is removed.
+        Ok(register.keys().cloned().collect())
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        let Ok(register) = self.0.read() else {
+            return Err(RegisterError::EntryFetchError(poisoned()));
+        };
+
+        Ok(register.values().flatten().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_entry(bot_id: &str, user_id: &str) -> CreateEntry {
+        CreateEntry {
+            user_id: String::from(user_id),
+            bot_id: String::from(bot_id),
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_empty() {
+        let register = InMemory::new();
+
+        let return_value = register.fetch(String::from("bot_id_12345")).await.unwrap();
+        assert!(return_value.is_empty())
+    }
+
+    #[tokio::test]
+    async fn test_add_and_fetch() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_12345", "user_id_0"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_12345", "user_id_1"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_67890", "user_id_2"))
+            .await
+            .unwrap();
+
+        let return_value = register.fetch(String::from("bot_id_12345")).await.unwrap();
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.bot_id, String::from("bot_id_12345"));
+            assert_eq!(entry.user_id, format!("user_id_{}", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_already_registered() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap();
+
+        let return_value = register
+            .add(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap_err();
+        assert_eq!(return_value, RegisterError::AlreadyRegistered);
+
+        let return_value = register.fetch(String::from("bot_id_12345")).await.unwrap();
+        assert_eq!(return_value.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let register = InMemory::new();
+
+        let return_value = register
+            .update(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered);
+
+        register
+            .add(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .update(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_0", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_67890"))
+            .await
+            .unwrap();
+
+        let payload = ListEntriesPayload {
+            user_id: String::from("user_id_12345"),
+        };
+
+        let mut return_value = register.list(payload).await.unwrap();
+        return_value.sort_by(|a, b| a.bot_id.cmp(&b.bot_id));
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.user_id, String::from("user_id_12345"));
+            assert_eq!(entry.bot_id, format!("bot_id_{}", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_12345", "user_id_12345"))
+            .await
+            .unwrap();
+
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        register.remove(entry).await.unwrap();
+        let return_value = register.fetch(String::from("bot_id_12345")).await.unwrap();
+        assert!(return_value.is_empty())
+    }
+
+    #[tokio::test]
+    async fn test_remove_not_registered() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_12345", "user_id_67890"))
+            .await
+            .unwrap();
+
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        let return_value = register.remove(entry).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered)
+    }
+
+    #[tokio::test]
+    async fn test_forget_user() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_0", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_67890"))
+            .await
+            .unwrap();
+
+        let return_value = register
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap();
+        assert_eq!(return_value, 2);
+
+        assert!(register.0.read().unwrap().get("bot_id_0").is_none());
+        let return_value = register.fetch(String::from("bot_id_1")).await.unwrap();
+        assert_eq!(return_value.len(), 1);
+        assert_eq!(return_value[0].user_id, String::from("user_id_67890"));
+    }
+
+    #[tokio::test]
+    async fn test_monitored_bots() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_0", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .remove(RemoveEntry {
+                bot_id: String::from("bot_id_1"),
+                user_id: String::from("user_id_12345"),
+            })
+            .await
+            .unwrap();
+
+        let return_value = register.monitored_bots().await.unwrap();
+        assert_eq!(return_value, HashSet::from([String::from("bot_id_0")]));
+    }
+
+    #[tokio::test]
+    async fn test_registrants() {
+        let register = InMemory::new();
+        register
+            .add(create_entry("bot_id_0", "user_id_12345"))
+            .await
+            .unwrap();
+        register
+            .add(create_entry("bot_id_1", "user_id_12345"))
+            .await
+            .unwrap();
+
+        let return_value = register.registrants().await.unwrap();
+        assert_eq!(return_value, HashSet::from([String::from("user_id_12345")]));
+    }
+}
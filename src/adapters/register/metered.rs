@@ -0,0 +1,155 @@
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use crate::health::health;
+use crate::metrics::metrics;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Records how long each call to the wrapped register takes and which ones
+/// fail, and whether the last one succeeded for `/readyz`.
+pub struct Metered<R> {
+    inner: R,
+}
+
+impl<R> Metered<R>
+where
+    R: Register,
+{
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+async fn observe<T>(
+    operation: &str,
+    call: impl Future<Output = Result<T, RegisterError>>,
+) -> Result<T, RegisterError> {
+    let started = Instant::now();
+    let result = call
+        .instrument(tracing::info_span!("register", operation))
+        .await;
+
+    metrics()
+        .register_latency
+        .with_label_values(&[operation])
+        .observe(started.elapsed().as_secs_f64());
+    // Finding an entry missing or already there is an answer, not a failure.
+    let failed = matches!(
+        &result,
+        Err(why) if !matches!(why, RegisterError::NotRegistered | RegisterError::AlreadyRegistered)
+    );
+    if failed {
+        metrics()
+            .register_errors
+            .with_label_values(&[operation])
+            .inc();
+    }
+    health().register_call(!failed);
+
+    result
+}
+
+#[async_trait]
+impl<R> Register for Metered<R>
+where
+    R: Register + Send + Sync,
+{
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        observe("fetch", self.inner.fetch(bot_id)).await
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        observe("add", self.inner.add(entry)).await
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        observe("update", self.inner.update(entry)).await
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        observe("remove", self.inner.remove(entry)).await
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        observe("list", self.inner.list(entry)).await
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        observe("forget_user", self.inner.forget_user(user_id)).await
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        observe("monitored_bots", self.inner.monitored_bots()).await
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        observe("registrants", self.inner.registrants()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+
+    #[tokio::test]
+    async fn test_errors_are_counted() {
+        let errors = metrics()
+            .register_errors
+            .with_label_values(&["forget_user"]);
+        let latency = metrics()
+            .register_latency
+            .with_label_values(&["forget_user"]);
+        let (errors_before, calls_before) = (errors.get(), latency.get_sample_count());
+
+        let mut register = MockRegister::new();
+        register
+            .expect_forget_user()
+            .times(1)
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::permanent("access denied"),
+            )));
+        register.expect_forget_user().times(1).return_const(Ok(1));
+
+        let metered = Metered::new(register);
+
+        assert!(
+            metered
+                .forget_user(String::from("user_id_0"))
+                .await
+                .is_err()
+        );
+        assert_eq!(metered.forget_user(String::from("user_id_1")).await, Ok(1));
+
+        assert_eq!(errors.get(), errors_before + 1);
+        assert_eq!(latency.get_sample_count(), calls_before + 2);
+    }
+
+    #[tokio::test]
+    async fn test_not_registered_is_not_an_error() {
+        let errors = metrics().register_errors.with_label_values(&["remove"]);
+        let errors_before = errors.get();
+
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .return_const(Err(RegisterError::NotRegistered));
+
+        let metered = Metered::new(register);
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        assert_eq!(
+            metered.remove(entry).await,
+            Err(RegisterError::NotRegistered)
+        );
+        assert_eq!(errors.get(), errors_before);
+    }
+}
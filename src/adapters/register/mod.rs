@@ -1,8 +1,108 @@
-use crate::adapters::register::dynamodb::DynamoDB;
+use crate::adapters::register::cache::Cached;
+use crate::adapters::register::dynamodb::{BootstrapError, DynamoDB};
+use crate::adapters::register::memory::InMemory;
+use crate::adapters::register::metered::Metered;
+use crate::adapters::register::postgres::Postgres;
+use crate::adapters::register::resilient::Resilient;
+use crate::adapters::register::sqlite::SQLite;
+use crate::config::{RegisterBackend, RegisterConfig};
 use crate::domain::register::Register;
+use thiserror::Error;
 
+mod cache;
 mod dynamodb;
+mod memory;
+mod metered;
+mod postgres;
+mod resilient;
+mod sql;
+mod sqlite;
+#[cfg(test)]
+mod testing;
 
-pub async fn init_register() -> impl Register {
-    DynamoDB::new().await
+pub type DynRegister = Box<dyn Register + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum MigrateError {
+    #[error("Database error - {0}")]
+    Sql(#[from] sqlx::Error),
+    #[error(transparent)]
+    Bootstrap(#[from] BootstrapError),
+}
+
+pub async fn init_register(config: &RegisterConfig) -> Result<DynRegister, MigrateError> {
+    let register = init_uncached_register(config).await?;
+
+    Ok(match config.cache_ttl {
+        Some(ttl) => {
+            let cached = Cached::new(register, ttl);
+            cached.load_monitored().await;
+            Box::new(cached)
+        }
+        None => register,
+    })
+}
+
+/// The register without the cache in front, for one-off commands where
+/// loading the monitored bots would cost more than it saves.
+pub async fn init_uncached_register(config: &RegisterConfig) -> Result<DynRegister, MigrateError> {
+    // Metered outside the retries, so latency and errors are what callers see.
+    Ok(Box::new(Metered::new(Resilient::new(
+        init_backend(&config.backend).await?,
+        &config.resilience,
+    ))))
+}
+
+/// Brings the backend's schema up to date without starting the bot: runs the
+/// SQL migrations, or creates the DynamoDB table and index if they are missing.
+pub async fn migrate(backend: &RegisterBackend) -> Result<(), MigrateError> {
+    match backend {
+        RegisterBackend::Memory => {
+            tracing::info!("The in memory register has nothing to migrate");
+        }
+        RegisterBackend::DynamoDB {
+            table_name,
+            endpoint_url,
+            ..
+        } => {
+            DynamoDB::new(table_name.clone(), endpoint_url.as_deref())
+                .await
+                .bootstrap()
+                .await?;
+        }
+        RegisterBackend::SQLite { url } => {
+            SQLite::new(url).await?;
+        }
+        RegisterBackend::Postgres { url, .. } => {
+            Postgres::new(url, 1).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn init_backend(backend: &RegisterBackend) -> Result<DynRegister, MigrateError> {
+    Ok(match backend {
+        RegisterBackend::Memory => {
+            tracing::warn!("Using the in memory register, entries will be lost on shutdown");
+            Box::new(InMemory::new())
+        }
+        RegisterBackend::DynamoDB {
+            table_name,
+            endpoint_url,
+            bootstrap,
+        } => {
+            let register = DynamoDB::new(table_name.clone(), endpoint_url.as_deref()).await;
+            if *bootstrap {
+                register.bootstrap().await?;
+            }
+            // `init_register` wraps every backend in `Resilient`, which does the retrying.
+            Box::new(register.without_retries())
+        }
+        RegisterBackend::SQLite { url } => Box::new(SQLite::new(url).await?),
+        RegisterBackend::Postgres {
+            url,
+            max_connections,
+        } => Box::new(Postgres::new(url, *max_connections).await?),
+    })
 }
@@ -0,0 +1,503 @@
+use crate::config::ResilienceConfig;
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError, StorageError};
+use crate::metrics::metrics;
+use async_trait::async_trait;
+use prometheus::IntGauge;
+use rand::Rng;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+enum Breaker {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A single trial call is in flight, other calls fail fast until it finishes or `until` passes.
+    HalfOpen {
+        until: Instant,
+    },
+}
+
+impl Breaker {
+    /// The value of the `register_circuit_breaker_state` gauge.
+    fn gauge(&self) -> i64 {
+        match self {
+            Self::Closed { .. } => 0,
+            Self::Open { .. } => 1,
+            Self::HalfOpen { .. } => 2,
+        }
+    }
+}
+
+/// Retries retryable failures of the wrapped register with jittered
+/// exponential backoff, and stops calling it for a while once enough calls in
+/// a row have failed that way so an outage fails fast instead of piling up.
+pub struct Resilient<R> {
+    inner: R,
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    threshold: u32,
+    open_for: Duration,
+    breaker: Mutex<Breaker>,
+    breaker_state: IntGauge,
+}
+
+impl<R> Resilient<R>
+where
+    R: Register,
+{
+    pub fn new(inner: R, config: &ResilienceConfig) -> Self {
+        Self {
+            inner,
+            attempts: config.retry_attempts.max(1),
+            base_delay: config.retry_base_delay,
+            max_delay: config.retry_max_delay,
+            threshold: config.breaker_threshold.max(1),
+            open_for: config.breaker_open_for,
+            breaker: Mutex::new(Breaker::Closed { failures: 0 }),
+            breaker_state: metrics().register_breaker_state.clone(),
+        }
+    }
+
+    fn transition(&self, breaker: &mut Breaker, to: Breaker) {
+        self.breaker_state.set(to.gauge());
+        *breaker = to;
+    }
+
+    fn admit(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+
+        match *breaker {
+            Breaker::Closed { .. } => true,
+            Breaker::Open { until } | Breaker::HalfOpen { until } if now >= until => {
+                tracing::info!("Register circuit breaker is half open, trying the register again");
+                self.transition(
+                    &mut breaker,
+                    Breaker::HalfOpen {
+                        until: now + self.open_for,
+                    },
+                );
+                true
+            }
+            Breaker::Open { .. } | Breaker::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record<T>(&self, result: &Result<T, RegisterError>) {
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Only failures that say the register is unavailable count, anything
+        // else means it answered.
+        let failed = result.as_ref().is_err_and(RegisterError::is_retryable);
+
+        match (&*breaker, failed) {
+            (Breaker::Closed { .. }, false) => *breaker = Breaker::Closed { failures: 0 },
+            (_, false) => {
+                tracing::info!("Register circuit breaker closed, the register is answering again");
+                self.transition(&mut breaker, Breaker::Closed { failures: 0 });
+            }
+            (Breaker::Closed { failures }, true) if failures + 1 < self.threshold => {
+                *breaker = Breaker::Closed {
+                    failures: failures + 1,
+                };
+            }
+            (_, true) => {
+                tracing::warn!(
+                    "Register circuit breaker opened for {:?} after repeated failures",
+                    self.open_for
+                );
+                self.transition(
+                    &mut breaker,
+                    Breaker::Open {
+                        until: Instant::now() + self.open_for,
+                    },
+                );
+            }
+        }
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .base_delay
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_delay);
+        let millis = u64::try_from(cap.as_millis()).unwrap_or(u64::MAX);
+
+        Duration::from_millis(rand::rng().random_range(0..=millis))
+    }
+
+    async fn call<T, F, Fut>(
+        &self,
+        unavailable: fn(StorageError) -> RegisterError,
+        mut operation: F,
+    ) -> Result<T, RegisterError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, RegisterError>>,
+    {
+        let mut retry = 0;
+        loop {
+            if !self.admit() {
+                return Err(unavailable(StorageError::retryable(
+                    "register circuit breaker is open",
+                )));
+            }
+
+            let result = operation().await;
+            self.record(&result);
+
+            match result {
+                Err(why) if why.is_retryable() && retry + 1 < self.attempts => {
+                    let delay = self.backoff(retry);
+                    tracing::debug!("Retrying register call in {delay:?} - {why}");
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Calls a conditional write. A retryable failure can hide an attempt that
+    /// went through, so its condition failing on a retry, as told by `done`,
+    /// means the write already happened.
+    async fn write<F, Fut>(
+        &self,
+        unavailable: fn(StorageError) -> RegisterError,
+        done: fn(&RegisterError) -> bool,
+        mut operation: F,
+    ) -> Result<(), RegisterError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<(), RegisterError>>,
+    {
+        let mut attempts = 0;
+        let result = self
+            .call(unavailable, || {
+                attempts += 1;
+                operation()
+            })
+            .await;
+
+        match result {
+            Err(why) if done(&why) && attempts > 1 => {
+                tracing::info!("Register write went through on an earlier attempt - {why}");
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+#[async_trait]
+impl<R> Register for Resilient<R>
+where
+    R: Register + Send + Sync,
+{
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        self.call(RegisterError::EntryFetchError, || {
+            self.inner.fetch(bot_id.clone())
+        })
+        .await
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        self.write(
+            RegisterError::EntryCreationError,
+            |why| matches!(why, RegisterError::AlreadyRegistered),
+            || self.inner.add(entry.clone()),
+        )
+        .await
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        self.call(RegisterError::EntryUpdateError, || {
+            self.inner.update(entry.clone())
+        })
+        .await
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        self.write(
+            RegisterError::EntryRemoveError,
+            |why| matches!(why, RegisterError::NotRegistered),
+            || self.inner.remove(entry.clone()),
+        )
+        .await
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        self.call(RegisterError::EntryFetchError, || {
+            self.inner.list(entry.clone())
+        })
+        .await
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        self.call(RegisterError::EntryRemoveError, || {
+            self.inner.forget_user(user_id.clone())
+        })
+        .await
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        self.call(RegisterError::EntryFetchError, || {
+            self.inner.monitored_bots()
+        })
+        .await
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        self.call(RegisterError::EntryFetchError, || self.inner.registrants())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::MockRegister;
+    use mockall::Sequence;
+
+    fn is_open<R: Register>(resilient: &Resilient<R>) -> bool {
+        matches!(*resilient.breaker.lock().unwrap(), Breaker::Open { .. })
+    }
+
+    fn config() -> ResilienceConfig {
+        ResilienceConfig {
+            retry_attempts: 3,
+            retry_base_delay: Duration::from_millis(50),
+            retry_max_delay: Duration::from_secs(2),
+            breaker_threshold: 3,
+            breaker_open_for: Duration::from_secs(30),
+        }
+    }
+
+    fn throttled() -> RegisterError {
+        RegisterError::EntryFetchError(StorageError::retryable("throttled"))
+    }
+
+    fn entries() -> Vec<RegisterEntry> {
+        vec![RegisterEntry {
+            bot_id: String::from("bot_id_12345"),
+            user_id: String::from("user_id_12345"),
+        }]
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_until_success() {
+        let mut sequence = Sequence::new();
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(throttled()));
+        register
+            .expect_fetch()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(entries()));
+
+        let resilient = Resilient::new(register, &config());
+
+        let return_value = resilient.fetch(String::from("bot_id_12345")).await;
+        assert_eq!(return_value, Ok(entries()));
+        assert!(!is_open(&resilient));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_attempts() {
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(3)
+            .returning(|_| Err(throttled()));
+
+        let resilient = Resilient::new(
+            register,
+            &ResilienceConfig {
+                breaker_threshold: 10,
+                ..config()
+            },
+        );
+
+        let return_value = resilient.fetch(String::from("bot_id_12345")).await;
+        assert_eq!(return_value, Err(throttled()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_permanent_error_is_not_retried() {
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .returning(|_| Err(RegisterError::AlreadyRegistered));
+
+        let resilient = Resilient::new(register, &config());
+
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 0,
+        };
+        let return_value = resilient.add(entry).await;
+        assert_eq!(return_value, Err(RegisterError::AlreadyRegistered));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retried_add_already_registered_succeeds() {
+        let mut sequence = Sequence::new();
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| {
+                Err(RegisterError::EntryCreationError(StorageError::retryable(
+                    "timed out",
+                )))
+            });
+        register
+            .expect_add()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(RegisterError::AlreadyRegistered));
+
+        let resilient = Resilient::new(register, &config());
+
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 0,
+        };
+        assert_eq!(resilient.add(entry).await, Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retried_remove_not_registered_succeeds() {
+        let mut sequence = Sequence::new();
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| {
+                Err(RegisterError::EntryRemoveError(StorageError::retryable(
+                    "timed out",
+                )))
+            });
+        register
+            .expect_remove()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(RegisterError::NotRegistered));
+
+        let resilient = Resilient::new(register, &config());
+
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+        assert_eq!(resilient.remove(entry).await, Ok(()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_remove_not_registered_is_an_error() {
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .returning(|_| Err(RegisterError::NotRegistered));
+
+        let resilient = Resilient::new(register, &config());
+
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+        assert_eq!(
+            resilient.remove(entry).await,
+            Err(RegisterError::NotRegistered)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_breaker_opens_and_fails_fast() {
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(3)
+            .returning(|_| Err(throttled()));
+
+        let resilient = Resilient::new(register, &config());
+
+        assert!(resilient.fetch(String::from("bot_id_12345")).await.is_err());
+        assert!(is_open(&resilient));
+
+        let return_value = resilient.fetch(String::from("bot_id_12345")).await;
+        assert!(matches!(
+            return_value,
+            Err(RegisterError::EntryFetchError(_))
+        ));
+        assert!(return_value.unwrap_err().is_retryable());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_breaker_closes_after_successful_trial() {
+        let mut sequence = Sequence::new();
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(3)
+            .in_sequence(&mut sequence)
+            .returning(|_| Err(throttled()));
+        register
+            .expect_fetch()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(entries()));
+
+        let mut resilient = Resilient::new(register, &config());
+        resilient.breaker_state = IntGauge::new("breaker_state", "test").unwrap();
+
+        assert!(resilient.fetch(String::from("bot_id_12345")).await.is_err());
+        assert_eq!(resilient.breaker_state.get(), 1);
+        tokio::time::advance(Duration::from_secs(30)).await;
+
+        let return_value = resilient.fetch(String::from("bot_id_12345")).await;
+        assert_eq!(return_value, Ok(entries()));
+        assert!(!is_open(&resilient));
+        assert_eq!(resilient.breaker_state.get(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_trial_reopens_breaker() {
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(3)
+            .returning(|_| Err(throttled()));
+
+        let mut resilient = Resilient::new(register, &config());
+        resilient.breaker_state = IntGauge::new("breaker_state", "test").unwrap();
+
+        assert!(resilient.fetch(String::from("bot_id_12345")).await.is_err());
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(resilient.admit());
+        assert_eq!(resilient.breaker_state.get(), 2);
+
+        resilient.record::<()>(&Err(throttled()));
+        assert!(is_open(&resilient));
+        assert_eq!(resilient.breaker_state.get(), 1);
+    }
+}
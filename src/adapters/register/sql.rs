@@ -0,0 +1,21 @@
+use crate::domain::register::StorageError;
+
+/// SQLSTATEs (Postgres) and result codes (SQLite) for failures that clear up on their own,
+/// such as serialization conflicts, lock contention and the server restarting.
+const RETRYABLE_CODES: [&str; 8] = ["40001", "40P01", "53300", "57P01", "57P03", "5", "6", "261"];
+
+pub fn storage_error(why: sqlx::Error) -> StorageError {
+    let retryable = match &why {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::Protocol(_) => true,
+        sqlx::Error::Database(database_error) => database_error
+            .code()
+            .is_some_and(|code| RETRYABLE_CODES.contains(&code.as_ref())),
+        _ => false,
+    };
+
+    if retryable {
+        StorageError::retryable(why)
+    } else {
+        StorageError::permanent(why)
+    }
+}
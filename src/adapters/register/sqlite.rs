@@ -0,0 +1,471 @@
+use crate::adapters::register::sql::storage_error;
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError, StorageError};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::SqliteConnectOptions;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+pub struct SQLite(SqlitePool);
+
+impl SQLite {
+    pub async fn new(url: &str) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        MIGRATOR.run(&pool).await?;
+
+        Ok(Self(pool))
+    }
+}
+
+#[async_trait]
+impl Register for SQLite {
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        let query_op = sqlx::query_as::<_, (String,)>(
+            "SELECT user_id FROM register WHERE bot_id = ? ORDER BY user_id",
+        )
+        .bind(&bot_id)
+        .fetch_all(&self.0)
+        .await;
+
+        let results = match query_op {
+            Err(e) => {
+                tracing::error!("{e:?}");
+                return Err(RegisterError::EntryFetchError(storage_error(e)));
+            }
+            Ok(rows) => rows,
+        };
+
+        Ok(results
+            .into_iter()
+            .map(|(user_id,)| RegisterEntry {
+                user_id,
+                bot_id: bot_id.clone(),
+            })
+            .collect())
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let version = i64::try_from(entry.version)
+            .map_err(|why| RegisterError::EntryCreationError(StorageError::permanent(why)))?;
+
+        let query_op = sqlx::query(
+            "INSERT INTO register (bot_id, user_id, entry_version) VALUES (?, ?, ?)
+             ON CONFLICT (bot_id, user_id) DO NOTHING",
+        )
+        .bind(entry.bot_id)
+        .bind(entry.user_id)
+        .bind(version)
+        .execute(&self.0)
+        .await;
+
+        match query_op {
+            Err(why) => {
+                tracing::error!("failed to send add request: {why:?}");
+                Err(RegisterError::EntryCreationError(storage_error(why)))
+            }
+            Ok(result) if result.rows_affected() == 0 => Err(RegisterError::AlreadyRegistered),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        let version = i64::try_from(entry.version)
+            .map_err(|why| RegisterError::EntryUpdateError(StorageError::permanent(why)))?;
+
+        let query_op =
+            sqlx::query("UPDATE register SET entry_version = ? WHERE bot_id = ? AND user_id = ?")
+                .bind(version)
+                .bind(entry.bot_id)
+                .bind(entry.user_id)
+                .execute(&self.0)
+                .await;
+
+        match query_op {
+            Err(why) => {
+                tracing::error!("failed to send update request: {why:?}");
+                Err(RegisterError::EntryUpdateError(storage_error(why)))
+            }
+            Ok(result) if result.rows_affected() == 0 => Err(RegisterError::NotRegistered),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        let query_op = sqlx::query("DELETE FROM register WHERE bot_id = ? AND user_id = ?")
+            .bind(entry.bot_id)
+            .bind(entry.user_id)
+            .execute(&self.0)
+            .await;
+
+        match query_op {
+            Err(why) => {
+                tracing::error!("failed to send delete request: {why:?}");
+                Err(RegisterError::EntryRemoveError(storage_error(why)))
+            }
+            Ok(result) if result.rows_affected() == 0 => Err(RegisterError::NotRegistered),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        let query_op = sqlx::query_as::<_, (String,)>(
+            "SELECT bot_id FROM register WHERE user_id = ? ORDER BY bot_id",
+        )
+        .bind(&entry.user_id)
+        .fetch_all(&self.0)
+        .await;
+
+        let results = match query_op {
+            Err(e) => {
+                tracing::error!("{e:?}");
+                return Err(RegisterError::EntryFetchError(storage_error(e)));
+            }
+            Ok(rows) => rows,
+        };
+
+        Ok(results
+            .into_iter()
+            .map(|(bot_id,)| RegisterEntry {
+                user_id: entry.user_id.clone(),
+                bot_id,
+            })
+            .collect())
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        let query_op = sqlx::query("DELETE FROM register WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.0)
+            .await;
+
+        match query_op {
+            Err(why) => {
+                tracing::error!("failed to send forget request: {why:?}");
+                Err(RegisterError::EntryRemoveError(storage_error(why)))
+            }
+            Ok(result) => usize::try_from(result.rows_affected())
+                .map_err(|why| RegisterError::EntryRemoveError(StorageError::permanent(why))),
+        }
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        let query_op = sqlx::query_as::<_, (String,)>("SELECT DISTINCT bot_id FROM register")
+            .fetch_all(&self.0)
+            .await;
+
+        match query_op {
+            Err(e) => {
+                tracing::error!("{e:?}");
+                Err(RegisterError::EntryFetchError(storage_error(e)))
+            }
+            Ok(rows) => Ok(rows.into_iter().map(|(bot_id,)| bot_id).collect()),
+        }
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        let query_op = sqlx::query_as::<_, (String,)>("SELECT DISTINCT user_id FROM register")
+            .fetch_all(&self.0)
+            .await;
+
+        match query_op {
+            Err(e) => {
+                tracing::error!("{e:?}");
+                Err(RegisterError::EntryFetchError(storage_error(e)))
+            }
+            Ok(rows) => Ok(rows.into_iter().map(|(user_id,)| user_id).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn sqlite_register() -> SQLite {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:").unwrap();
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+
+        SQLite(pool)
+    }
+
+    async fn insert(register: &SQLite, bot_id: &str, user_id: &str) {
+        sqlx::query("INSERT INTO register (bot_id, user_id, entry_version) VALUES (?, ?, 0)")
+            .bind(bot_id)
+            .bind(user_id)
+            .execute(&register.0)
+            .await
+            .unwrap();
+    }
+
+    async fn count(register: &SQLite) -> i64 {
+        let (count,) = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM register")
+            .fetch_one(&register.0)
+            .await
+            .unwrap();
+        count
+    }
+
+    async fn version(register: &SQLite) -> i64 {
+        let (version,) = sqlx::query_as::<_, (i64,)>("SELECT entry_version FROM register")
+            .fetch_one(&register.0)
+            .await
+            .unwrap();
+        version
+    }
+
+    #[tokio::test]
+    async fn test_list_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+        let payload = ListEntriesPayload {
+            user_id: String::from("user_id_12345"),
+        };
+
+        let return_value = register.list(payload).await.unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)));
+        assert!(!return_value.is_retryable())
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let user_id = String::from("user_id_12345");
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_0", &user_id).await;
+        insert(&register, "bot_id_1", &user_id).await;
+        insert(&register, "bot_id_2", "user_id_67890").await;
+        let payload = ListEntriesPayload {
+            user_id: user_id.clone(),
+        };
+
+        let return_value = register.list(payload).await.unwrap();
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.user_id, user_id.clone());
+            assert_eq!(entry.bot_id, format!("bot_id_{}", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        let return_value = register.remove(entry).await.unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryRemoveError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_remove_missing_entry() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_12345", "user_id_67890").await;
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        let return_value = register.remove(entry).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered);
+        assert_eq!(count(&register).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_12345", "user_id_12345").await;
+        insert(&register, "bot_id_12345", "user_id_67890").await;
+        let entry = RemoveEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+        };
+
+        register.remove(entry).await.unwrap();
+        assert_eq!(count(&register).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+
+        let return_value = register
+            .fetch(String::from("bot_id_12345"))
+            .await
+            .unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryFetchError(_)));
+        assert!(!return_value.is_retryable())
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        let bot_id = String::from("bot_id_12345");
+        let register = sqlite_register().await;
+        insert(&register, &bot_id, "user_id_0").await;
+        insert(&register, &bot_id, "user_id_1").await;
+        insert(&register, "bot_id_67890", "user_id_2").await;
+
+        let return_value = register.fetch(bot_id.clone()).await.unwrap();
+        assert_eq!(return_value.len(), 2);
+        for (i, entry) in return_value.iter().enumerate() {
+            assert_eq!(entry.bot_id, bot_id.clone());
+            assert_eq!(entry.user_id, format!("user_id_{}", i));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add() {
+        let register = sqlite_register().await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 0,
+        };
+
+        register.add(entry).await.unwrap();
+        assert_eq!(count(&register).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_already_registered() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_12345", "user_id_12345").await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        let return_value = register.add(entry).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::AlreadyRegistered);
+        assert_eq!(version(&register).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_12345", "user_id_12345").await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        register.update(entry).await.unwrap();
+        assert_eq!(version(&register).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_not_registered() {
+        let register = sqlite_register().await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        let return_value = register.update(entry).await.unwrap_err();
+        assert_eq!(return_value, RegisterError::NotRegistered);
+        assert_eq!(count(&register).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_update_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 1,
+        };
+
+        let return_value = register.update(entry).await.unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryUpdateError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_add_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+        let entry = CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from("bot_id_12345"),
+            version: 0,
+        };
+
+        let return_value = register.add(entry).await.unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryCreationError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_forget_user() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_0", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_67890").await;
+
+        let return_value = register
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap();
+        assert_eq!(return_value, 2);
+        assert_eq!(count(&register).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_forget_user_error() {
+        let register = sqlite_register().await;
+        register.0.close().await;
+
+        let return_value = register
+            .forget_user(String::from("user_id_12345"))
+            .await
+            .unwrap_err();
+        assert!(matches!(return_value, RegisterError::EntryRemoveError(_)))
+    }
+
+    #[tokio::test]
+    async fn test_monitored_bots() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_0", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_67890").await;
+
+        let return_value = register.monitored_bots().await.unwrap();
+        assert_eq!(
+            return_value,
+            HashSet::from([String::from("bot_id_0"), String::from("bot_id_1")])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registrants() {
+        let register = sqlite_register().await;
+        insert(&register, "bot_id_0", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_12345").await;
+        insert(&register, "bot_id_1", "user_id_67890").await;
+
+        let return_value = register.registrants().await.unwrap();
+        assert_eq!(
+            return_value,
+            HashSet::from([String::from("user_id_12345"), String::from("user_id_67890")])
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static IDS: AtomicUsize = AtomicUsize::new(0);
+
+/// An id no other test run or test in this one uses, so tests against a
+/// shared database don't see each other's entries.
+pub fn unique_id(prefix: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("{prefix}_{nanos}_{}", IDS.fetch_add(1, Ordering::Relaxed))
+}
@@ -0,0 +1,1109 @@
+use crate::domain::app::CleanupPolicy;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Could not read config file {path:?} - {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not parse config file {path:?} - {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Missing required setting `{key}`, set it in the config file or with {env}")]
+    Missing {
+        key: &'static str,
+        env: &'static str,
+    },
+    #[error("Invalid value '{value}' for `{key}` - {reason}")]
+    Invalid {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+pub struct Config {
+    pub discord: DiscordConfig,
+    pub register: RegisterConfig,
+    pub cleanup: CleanupPolicy,
+    pub shutdown: ShutdownConfig,
+    pub http: HttpConfig,
+    pub log_format: LogFormat,
+    pub telemetry: TelemetryConfig,
+    pub admin: AdminConfig,
+}
+
+/// How short an admin API token may be, so a guessable one is rejected at startup.
+const MIN_API_TOKEN_LEN: usize = 16;
+
+pub struct AdminConfig {
+    /// The bearer tokens the admin API accepts, none turns the API off.
+    pub api_tokens: Vec<String>,
+}
+
+pub struct TelemetryConfig {
+    /// The OTLP/HTTP collector traces are exported to, `None` turns export off.
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+    /// The share of traces that are kept, from 0 to 1.
+    pub sample_ratio: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of every enclosing span.
+    Json,
+}
+
+pub struct DiscordConfig {
+    pub token: String,
+    pub sharding: Sharding,
+    /// A guild slash commands are synced to instead of globally, for development.
+    pub commands_guild: Option<u64>,
+}
+
+/// Which gateway shards this process runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sharding {
+    Single,
+    /// As many shards as Discord recommends, all in this process.
+    Auto,
+    /// Shards `first` to `last` inclusive out of `total`, the rest are run by other processes.
+    Range {
+        first: u32,
+        last: u32,
+        total: u32,
+    },
+}
+
+pub struct HttpConfig {
+    /// Where `/metrics` is served, `None` turns the HTTP server off.
+    pub listen_addr: Option<SocketAddr>,
+}
+
+pub struct ShutdownConfig {
+    /// How long in flight events get to finish once a shutdown signal arrives.
+    pub timeout: Duration,
+    /// Where flagged orphans are kept between restarts, `None` forgets them on shutdown.
+    pub state_file: Option<PathBuf>,
+}
+
+pub struct RegisterConfig {
+    pub backend: RegisterBackend,
+    /// How long the monitored bots and `fetch` results are cached for, `None`
+    /// turns the cache off.
+    pub cache_ttl: Option<Duration>,
+    pub resilience: ResilienceConfig,
+}
+
+/// Retries and circuit breaking around every call to the register backend.
+pub struct ResilienceConfig {
+    pub retry_attempts: u32,
+    pub retry_base_delay: Duration,
+    pub retry_max_delay: Duration,
+    /// Consecutive retryable failures before the breaker opens.
+    pub breaker_threshold: u32,
+    /// How long an open breaker fails calls before letting one through to test the backend.
+    pub breaker_open_for: Duration,
+}
+
+pub enum RegisterBackend {
+    DynamoDB {
+        table_name: String,
+        endpoint_url: Option<String>,
+        /// Create the table and its index at startup if they are missing.
+        bootstrap: bool,
+    },
+    SQLite {
+        url: String,
+    },
+    Postgres {
+        url: String,
+        max_connections: u32,
+    },
+    Memory,
+}
+
+/// A setting's key in the config file and the env var that overrides it.
+struct Setting {
+    key: &'static str,
+    env: &'static str,
+}
+
+const BOT_TOKEN: Setting = Setting {
+    key: "discord.token",
+    env: "BOT_TOKEN",
+};
+const DISCORD_SHARDING: Setting = Setting {
+    key: "discord.sharding",
+    env: "DISCORD_SHARDING",
+};
+const DISCORD_SHARD_RANGE: Setting = Setting {
+    key: "discord.shard_range",
+    env: "DISCORD_SHARD_RANGE",
+};
+const DISCORD_SHARD_TOTAL: Setting = Setting {
+    key: "discord.shard_total",
+    env: "DISCORD_SHARD_TOTAL",
+};
+const DISCORD_COMMANDS_GUILD: Setting = Setting {
+    key: "discord.commands_guild",
+    env: "DISCORD_COMMANDS_GUILD",
+};
+const REGISTER_BACKEND: Setting = Setting {
+    key: "register.backend",
+    env: "REGISTER_BACKEND",
+};
+const TABLE_NAME: Setting = Setting {
+    key: "register.table_name",
+    env: "TABLE_NAME",
+};
+const DYNAMODB_ENDPOINT_URL: Setting = Setting {
+    key: "register.endpoint_url",
+    env: "DYNAMODB_ENDPOINT_URL",
+};
+const DYNAMODB_BOOTSTRAP_TABLE: Setting = Setting {
+    key: "register.bootstrap_table",
+    env: "DYNAMODB_BOOTSTRAP_TABLE",
+};
+const DATABASE_URL: Setting = Setting {
+    key: "register.database_url",
+    env: "DATABASE_URL",
+};
+const DATABASE_MAX_CONNECTIONS: Setting = Setting {
+    key: "register.max_connections",
+    env: "DATABASE_MAX_CONNECTIONS",
+};
+const REGISTER_CACHE_TTL_SECS: Setting = Setting {
+    key: "register.cache_ttl_secs",
+    env: "REGISTER_CACHE_TTL_SECS",
+};
+const REGISTER_RETRY_ATTEMPTS: Setting = Setting {
+    key: "register.retry_attempts",
+    env: "REGISTER_RETRY_ATTEMPTS",
+};
+const REGISTER_RETRY_BASE_DELAY_MS: Setting = Setting {
+    key: "register.retry_base_delay_ms",
+    env: "REGISTER_RETRY_BASE_DELAY_MS",
+};
+const REGISTER_RETRY_MAX_DELAY_MS: Setting = Setting {
+    key: "register.retry_max_delay_ms",
+    env: "REGISTER_RETRY_MAX_DELAY_MS",
+};
+const REGISTER_BREAKER_THRESHOLD: Setting = Setting {
+    key: "register.breaker_threshold",
+    env: "REGISTER_BREAKER_THRESHOLD",
+};
+const REGISTER_BREAKER_OPEN_SECS: Setting = Setting {
+    key: "register.breaker_open_secs",
+    env: "REGISTER_BREAKER_OPEN_SECS",
+};
+const ORPHAN_GRACE_PERIOD_HOURS: Setting = Setting {
+    key: "cleanup.grace_period_hours",
+    env: "ORPHAN_GRACE_PERIOD_HOURS",
+};
+const ORPHAN_DM_FAILURE_THRESHOLD: Setting = Setting {
+    key: "cleanup.dm_failure_threshold",
+    env: "ORPHAN_DM_FAILURE_THRESHOLD",
+};
+const ORPHAN_SWEEP_INTERVAL_SECS: Setting = Setting {
+    key: "cleanup.sweep_interval_secs",
+    env: "ORPHAN_SWEEP_INTERVAL_SECS",
+};
+const SHUTDOWN_TIMEOUT_SECS: Setting = Setting {
+    key: "shutdown.timeout_secs",
+    env: "SHUTDOWN_TIMEOUT_SECS",
+};
+const ORPHAN_STATE_FILE: Setting = Setting {
+    key: "shutdown.state_file",
+    env: "ORPHAN_STATE_FILE",
+};
+const HTTP_LISTEN_ADDR: Setting = Setting {
+    key: "http.listen_addr",
+    env: "HTTP_LISTEN_ADDR",
+};
+const LOG_FORMAT: Setting = Setting {
+    key: "logging.format",
+    env: "LOG_FORMAT",
+};
+const OTLP_ENDPOINT: Setting = Setting {
+    key: "telemetry.otlp_endpoint",
+    env: "OTEL_EXPORTER_OTLP_ENDPOINT",
+};
+const OTEL_SERVICE_NAME: Setting = Setting {
+    key: "telemetry.service_name",
+    env: "OTEL_SERVICE_NAME",
+};
+const TRACE_SAMPLE_RATIO: Setting = Setting {
+    key: "telemetry.sample_ratio",
+    env: "TRACE_SAMPLE_RATIO",
+};
+const ADMIN_API_TOKENS: Setting = Setting {
+    key: "admin.api_tokens",
+    env: "ADMIN_API_TOKENS",
+};
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    discord: DiscordFile,
+    register: RegisterFile,
+    cleanup: CleanupFile,
+    shutdown: ShutdownFile,
+    http: HttpFile,
+    logging: LoggingFile,
+    telemetry: TelemetryFile,
+    admin: AdminFile,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct DiscordFile {
+    token: Option<String>,
+    sharding: Option<String>,
+    shard_range: Option<String>,
+    shard_total: Option<u32>,
+    commands_guild: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RegisterFile {
+    backend: Option<String>,
+    table_name: Option<String>,
+    endpoint_url: Option<String>,
+    bootstrap_table: Option<bool>,
+    database_url: Option<String>,
+    max_connections: Option<u32>,
+    cache_ttl_secs: Option<u64>,
+    retry_attempts: Option<u32>,
+    retry_base_delay_ms: Option<u64>,
+    retry_max_delay_ms: Option<u64>,
+    breaker_threshold: Option<u32>,
+    breaker_open_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct CleanupFile {
+    grace_period_hours: Option<u64>,
+    dm_failure_threshold: Option<u32>,
+    sweep_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ShutdownFile {
+    timeout_secs: Option<u64>,
+    state_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct HttpFile {
+    listen_addr: Option<SocketAddr>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LoggingFile {
+    format: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AdminFile {
+    api_tokens: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct TelemetryFile {
+    otlp_endpoint: Option<String>,
+    service_name: Option<String>,
+    sample_ratio: Option<f64>,
+}
+
+impl FileConfig {
+    fn read(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+struct Sources<E> {
+    file: FileConfig,
+    env: E,
+}
+
+impl<E> Sources<E>
+where
+    E: Fn(&str) -> Option<String>,
+{
+    fn get<T>(&self, setting: &Setting, file_value: Option<T>) -> Result<Option<T>, ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(value) = (self.env)(setting.env) else {
+            return Ok(file_value);
+        };
+
+        value
+            .parse()
+            .map(Some)
+            .map_err(|why: T::Err| ConfigError::Invalid {
+                key: setting.key,
+                reason: why.to_string(),
+                value,
+            })
+    }
+
+    fn require<T>(&self, setting: &Setting, file_value: Option<T>) -> Result<T, ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.get(setting, file_value)?.ok_or(ConfigError::Missing {
+            key: setting.key,
+            env: setting.env,
+        })
+    }
+
+    fn discord(&self) -> Result<DiscordConfig, ConfigError> {
+        let token: String = self.require(&BOT_TOKEN, self.file.discord.token.clone())?;
+        if token.trim().is_empty() {
+            return Err(ConfigError::Invalid {
+                key: BOT_TOKEN.key,
+                value: token,
+                reason: String::from("the token cannot be empty"),
+            });
+        }
+
+        Ok(DiscordConfig {
+            token,
+            sharding: self.sharding()?,
+            commands_guild: self.get(&DISCORD_COMMANDS_GUILD, self.file.discord.commands_guild)?,
+        })
+    }
+
+    fn sharding(&self) -> Result<Sharding, ConfigError> {
+        let file = &self.file.discord;
+        let sharding: String = self
+            .get(&DISCORD_SHARDING, file.sharding.clone())?
+            .unwrap_or_else(|| String::from("single"));
+
+        match sharding.to_lowercase().as_str() {
+            "single" => Ok(Sharding::Single),
+            "auto" => Ok(Sharding::Auto),
+            "range" => {
+                let range: String = self.require(&DISCORD_SHARD_RANGE, file.shard_range.clone())?;
+                let total = self.require(&DISCORD_SHARD_TOTAL, file.shard_total)?;
+                let invalid = |reason: &str| ConfigError::Invalid {
+                    key: DISCORD_SHARD_RANGE.key,
+                    value: range.clone(),
+                    reason: String::from(reason),
+                };
+
+                let (first, last) = range
+                    .split_once('-')
+                    .and_then(|(first, last)| {
+                        Some((first.trim().parse().ok()?, last.trim().parse().ok()?))
+                    })
+                    .ok_or_else(|| invalid("expected the first and last shard, e.g. 0-3"))?;
+                if first > last {
+                    return Err(invalid("the first shard comes after the last"));
+                }
+                if last >= total {
+                    return Err(invalid(&format!(
+                        "shards are numbered from 0 to {}",
+                        total.saturating_sub(1)
+                    )));
+                }
+
+                Ok(Sharding::Range { first, last, total })
+            }
+            _ => Err(ConfigError::Invalid {
+                key: DISCORD_SHARDING.key,
+                value: sharding,
+                reason: String::from("expected one of single, auto or range"),
+            }),
+        }
+    }
+
+    fn register(&self) -> Result<RegisterConfig, ConfigError> {
+        let cache_ttl_secs = self
+            .get(&REGISTER_CACHE_TTL_SECS, self.file.register.cache_ttl_secs)?
+            .unwrap_or(300);
+
+        Ok(RegisterConfig {
+            backend: self.register_backend()?,
+            cache_ttl: (cache_ttl_secs > 0).then(|| Duration::from_secs(cache_ttl_secs)),
+            resilience: self.resilience()?,
+        })
+    }
+
+    fn resilience(&self) -> Result<ResilienceConfig, ConfigError> {
+        let file = &self.file.register;
+        let retry_attempts = self
+            .get(&REGISTER_RETRY_ATTEMPTS, file.retry_attempts)?
+            .unwrap_or(3);
+        let retry_base_delay = self
+            .get(&REGISTER_RETRY_BASE_DELAY_MS, file.retry_base_delay_ms)?
+            .unwrap_or(50);
+        let retry_max_delay = self
+            .get(&REGISTER_RETRY_MAX_DELAY_MS, file.retry_max_delay_ms)?
+            .unwrap_or(2000);
+        let breaker_threshold = self
+            .get(&REGISTER_BREAKER_THRESHOLD, file.breaker_threshold)?
+            .unwrap_or(5);
+        let breaker_open_secs = self
+            .get(&REGISTER_BREAKER_OPEN_SECS, file.breaker_open_secs)?
+            .unwrap_or(30);
+
+        for (setting, value) in [
+            (&REGISTER_RETRY_ATTEMPTS, retry_attempts),
+            (&REGISTER_BREAKER_THRESHOLD, breaker_threshold),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::Invalid {
+                    key: setting.key,
+                    value: value.to_string(),
+                    reason: String::from("must be at least 1"),
+                });
+            }
+        }
+
+        if retry_max_delay < retry_base_delay {
+            return Err(ConfigError::Invalid {
+                key: REGISTER_RETRY_MAX_DELAY_MS.key,
+                value: retry_max_delay.to_string(),
+                reason: format!(
+                    "must not be less than {} ({retry_base_delay})",
+                    REGISTER_RETRY_BASE_DELAY_MS.key
+                ),
+            });
+        }
+
+        Ok(ResilienceConfig {
+            retry_attempts,
+            retry_base_delay: Duration::from_millis(retry_base_delay),
+            retry_max_delay: Duration::from_millis(retry_max_delay),
+            breaker_threshold,
+            breaker_open_for: Duration::from_secs(breaker_open_secs),
+        })
+    }
+
+    fn cleanup(&self) -> Result<CleanupPolicy, ConfigError> {
+        let file = &self.file.cleanup;
+        let default = CleanupPolicy::default();
+
+        let grace_period = self
+            .get(&ORPHAN_GRACE_PERIOD_HOURS, file.grace_period_hours)?
+            .map_or(default.grace_period, |hours: u64| {
+                Duration::from_secs(hours.saturating_mul(60 * 60))
+            });
+        let delivery_failures = self
+            .get(&ORPHAN_DM_FAILURE_THRESHOLD, file.dm_failure_threshold)?
+            .unwrap_or(default.delivery_failures);
+        let sweep_interval_secs = self
+            .get(&ORPHAN_SWEEP_INTERVAL_SECS, file.sweep_interval_secs)?
+            .unwrap_or(default.sweep_interval.as_secs());
+
+        for (setting, value) in [
+            (&ORPHAN_DM_FAILURE_THRESHOLD, u64::from(delivery_failures)),
+            (&ORPHAN_SWEEP_INTERVAL_SECS, sweep_interval_secs),
+        ] {
+            if value == 0 {
+                return Err(ConfigError::Invalid {
+                    key: setting.key,
+                    value: value.to_string(),
+                    reason: String::from("must be at least 1"),
+                });
+            }
+        }
+
+        Ok(CleanupPolicy {
+            grace_period,
+            delivery_failures,
+            sweep_interval: Duration::from_secs(sweep_interval_secs),
+        })
+    }
+
+    fn shutdown(&self) -> Result<ShutdownConfig, ConfigError> {
+        let file = &self.file.shutdown;
+        let timeout_secs = self
+            .get(&SHUTDOWN_TIMEOUT_SECS, file.timeout_secs)?
+            .unwrap_or(8);
+
+        Ok(ShutdownConfig {
+            timeout: Duration::from_secs(timeout_secs),
+            state_file: self.get(&ORPHAN_STATE_FILE, file.state_file.clone())?,
+        })
+    }
+
+    fn http(&self) -> Result<HttpConfig, ConfigError> {
+        Ok(HttpConfig {
+            listen_addr: self.get(&HTTP_LISTEN_ADDR, self.file.http.listen_addr)?,
+        })
+    }
+
+    fn log_format(&self) -> Result<LogFormat, ConfigError> {
+        let Some(format) = self.get::<String>(&LOG_FORMAT, self.file.logging.format.clone())?
+        else {
+            return Ok(LogFormat::default());
+        };
+
+        match format.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(ConfigError::Invalid {
+                key: LOG_FORMAT.key,
+                value: format,
+                reason: String::from("expected text or json"),
+            }),
+        }
+    }
+
+    fn telemetry(&self) -> Result<TelemetryConfig, ConfigError> {
+        let file = &self.file.telemetry;
+        let sample_ratio = self
+            .get(&TRACE_SAMPLE_RATIO, file.sample_ratio)?
+            .unwrap_or(1.0);
+        if !(0.0..=1.0).contains(&sample_ratio) {
+            return Err(ConfigError::Invalid {
+                key: TRACE_SAMPLE_RATIO.key,
+                value: sample_ratio.to_string(),
+                reason: String::from("must be between 0 and 1"),
+            });
+        }
+
+        Ok(TelemetryConfig {
+            otlp_endpoint: self.get(&OTLP_ENDPOINT, file.otlp_endpoint.clone())?,
+            service_name: self
+                .get(&OTEL_SERVICE_NAME, file.service_name.clone())?
+                .unwrap_or_else(|| String::from("bot-monitor")),
+            sample_ratio,
+        })
+    }
+
+    fn admin(&self) -> Result<AdminConfig, ConfigError> {
+        // A list in the file, comma separated in the env var.
+        let api_tokens = match (self.env)(ADMIN_API_TOKENS.env) {
+            Some(tokens) => tokens
+                .split(',')
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(String::from)
+                .collect(),
+            None => self.file.admin.api_tokens.clone().unwrap_or_default(),
+        };
+
+        if let Some(token) = api_tokens
+            .iter()
+            .find(|token| token.len() < MIN_API_TOKEN_LEN)
+        {
+            return Err(ConfigError::Invalid {
+                key: ADMIN_API_TOKENS.key,
+                value: format!("{}...", token.chars().take(4).collect::<String>()),
+                reason: format!("tokens must be at least {MIN_API_TOKEN_LEN} characters long"),
+            });
+        }
+
+        Ok(AdminConfig { api_tokens })
+    }
+
+    fn register_backend(&self) -> Result<RegisterBackend, ConfigError> {
+        let file = &self.file.register;
+        let backend: String = self
+            .get(&REGISTER_BACKEND, file.backend.clone())?
+            .unwrap_or_else(|| String::from("dynamodb"));
+
+        match backend.to_lowercase().as_str() {
+            "dynamodb" => Ok(RegisterBackend::DynamoDB {
+                table_name: self.require(&TABLE_NAME, file.table_name.clone())?,
+                endpoint_url: self.get(&DYNAMODB_ENDPOINT_URL, file.endpoint_url.clone())?,
+                bootstrap: self
+                    .get(&DYNAMODB_BOOTSTRAP_TABLE, file.bootstrap_table)?
+                    .unwrap_or(false),
+            }),
+            "sqlite" => Ok(RegisterBackend::SQLite {
+                url: self
+                    .get(&DATABASE_URL, file.database_url.clone())?
+                    .unwrap_or_else(|| String::from("sqlite://register.db")),
+            }),
+            "postgres" => {
+                let max_connections = self
+                    .get(&DATABASE_MAX_CONNECTIONS, file.max_connections)?
+                    .unwrap_or(5);
+                if max_connections == 0 {
+                    return Err(ConfigError::Invalid {
+                        key: DATABASE_MAX_CONNECTIONS.key,
+                        value: max_connections.to_string(),
+                        reason: String::from("the pool needs at least one connection"),
+                    });
+                }
+
+                Ok(RegisterBackend::Postgres {
+                    url: self.require(&DATABASE_URL, file.database_url.clone())?,
+                    max_connections,
+                })
+            }
+            "memory" => Ok(RegisterBackend::Memory),
+            _ => Err(ConfigError::Invalid {
+                key: REGISTER_BACKEND.key,
+                value: backend,
+                reason: String::from("expected one of dynamodb, sqlite, postgres or memory"),
+            }),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config from the TOML file named by `CONFIG_FILE`, if there is one,
+    /// with any env vars taking precedence over the file.
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = match env::var("CONFIG_FILE") {
+            Ok(path) => FileConfig::read(Path::new(&path))?,
+            Err(_) => FileConfig::default(),
+        };
+
+        Self::from_sources(file, |key| env::var(key).ok())
+    }
+
+    fn from_sources<E>(file: FileConfig, env: E) -> Result<Self, ConfigError>
+    where
+        E: Fn(&str) -> Option<String>,
+    {
+        let sources = Sources { file, env };
+
+        Ok(Self {
+            discord: sources.discord()?,
+            register: sources.register()?,
+            cleanup: sources.cleanup()?,
+            shutdown: sources.shutdown()?,
+            http: sources.http()?,
+            log_format: sources.log_format()?,
+            telemetry: sources.telemetry()?,
+            admin: sources.admin()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(file: &str, env: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let file: FileConfig = toml::from_str(file).unwrap();
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (String::from(*key), String::from(*value)))
+            .collect();
+
+        Config::from_sources(file, |key| env.get(key).cloned())
+    }
+
+    fn load_error(file: &str, env: &[(&str, &str)]) -> ConfigError {
+        match load(file, env) {
+            Ok(_) => panic!("expected the config to be invalid"),
+            Err(why) => why,
+        }
+    }
+
+    #[test]
+    fn test_env_only() {
+        let config = load("", &[("BOT_TOKEN", "token"), ("TABLE_NAME", "table")]).unwrap();
+
+        assert_eq!(config.discord.token, "token");
+        assert!(matches!(
+            config.register.backend,
+            RegisterBackend::DynamoDB { table_name, endpoint_url: None, bootstrap: false } if table_name == "table"
+        ));
+    }
+
+    #[test]
+    fn test_dynamodb_local() {
+        let file = r#"
+            [register]
+            table_name = "table"
+            endpoint_url = "http://localhost:8000"
+        "#;
+
+        let config = load(
+            file,
+            &[("BOT_TOKEN", "token"), ("DYNAMODB_BOOTSTRAP_TABLE", "true")],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.register.backend,
+            RegisterBackend::DynamoDB { endpoint_url: Some(url), bootstrap: true, .. } if url == "http://localhost:8000"
+        ));
+    }
+
+    #[test]
+    fn test_file_only() {
+        let file = r#"
+            [discord]
+            token = "token"
+
+            [register]
+            backend = "postgres"
+            database_url = "postgres://localhost/register"
+            max_connections = 10
+        "#;
+
+        let config = load(file, &[]).unwrap();
+
+        assert_eq!(config.discord.token, "token");
+        assert!(matches!(
+            config.register.backend,
+            RegisterBackend::Postgres { url, max_connections: 10 } if url == "postgres://localhost/register"
+        ));
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let file = r#"
+            [discord]
+            token = "file_token"
+
+            [register]
+            backend = "dynamodb"
+            table_name = "table"
+        "#;
+
+        let config = load(
+            file,
+            &[("BOT_TOKEN", "env_token"), ("REGISTER_BACKEND", "SQLite")],
+        )
+        .unwrap();
+
+        assert_eq!(config.discord.token, "env_token");
+        assert!(matches!(
+            config.register.backend,
+            RegisterBackend::SQLite { url } if url == "sqlite://register.db"
+        ));
+    }
+
+    #[test]
+    fn test_cache_ttl() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.register.cache_ttl, Some(Duration::from_secs(300)));
+
+        let config = load("[register]\ncache_ttl_secs = 10", &env).unwrap();
+        assert_eq!(config.register.cache_ttl, Some(Duration::from_secs(10)));
+
+        let config = load("[register]\ncache_ttl_secs = 0", &env).unwrap();
+        assert_eq!(config.register.cache_ttl, None);
+    }
+
+    #[test]
+    fn test_resilience() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("[register]\nretry_attempts = 4", &env).unwrap();
+        assert_eq!(config.register.resilience.retry_attempts, 4);
+        assert_eq!(config.register.resilience.breaker_threshold, 5);
+
+        let error = load_error(
+            "[register]\nretry_base_delay_ms = 500\nretry_max_delay_ms = 100",
+            &env,
+        );
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "register.retry_max_delay_ms",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_cleanup() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("[cleanup]\ngrace_period_hours = 24", &env).unwrap();
+        assert_eq!(
+            config.cleanup.grace_period,
+            Duration::from_secs(24 * 60 * 60)
+        );
+        assert_eq!(config.cleanup.delivery_failures, 3);
+
+        let error = load_error("[cleanup]\nsweep_interval_secs = 0", &env);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "cleanup.sweep_interval_secs",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_commands_guild() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.discord.commands_guild, None);
+
+        let config = load("[discord]\ncommands_guild = 1234", &env).unwrap();
+        assert_eq!(config.discord.commands_guild, Some(1234));
+
+        let error = load_error("", &[("DISCORD_COMMANDS_GUILD", "dev"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "discord.commands_guild",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_sharding() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.discord.sharding, Sharding::Single);
+
+        let config = load("[discord]\nsharding = \"auto\"", &env).unwrap();
+        assert_eq!(config.discord.sharding, Sharding::Auto);
+
+        let file = "[discord]\nsharding = \"range\"\nshard_range = \"4-7\"\nshard_total = 10";
+        let config = load(file, &env).unwrap();
+        assert_eq!(
+            config.discord.sharding,
+            Sharding::Range {
+                first: 4,
+                last: 7,
+                total: 10
+            }
+        );
+
+        let error = load_error(file, &[("DISCORD_SHARD_TOTAL", "6"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "discord.shard_range",
+                ..
+            }
+        ));
+
+        let error = load_error("[discord]\nsharding = \"range\"", &env);
+        assert!(matches!(
+            error,
+            ConfigError::Missing {
+                key: "discord.shard_range",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_http() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.http.listen_addr, None);
+
+        let config = load("[http]\nlisten_addr = \"0.0.0.0:9100\"", &env).unwrap();
+        assert_eq!(
+            config.http.listen_addr,
+            Some(SocketAddr::from(([0, 0, 0, 0], 9100)))
+        );
+
+        let error = load_error("", &[("HTTP_LISTEN_ADDR", "9100"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "http.listen_addr",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_log_format() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        assert_eq!(load("", &env).unwrap().log_format, LogFormat::Text);
+
+        let config = load("[logging]\nformat = \"JSON\"", &env).unwrap();
+        assert_eq!(config.log_format, LogFormat::Json);
+
+        let error = load_error("", &[("LOG_FORMAT", "xml"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "logging.format",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_admin() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert!(config.admin.api_tokens.is_empty());
+
+        let file = "[admin]\napi_tokens = [\"0123456789abcdef\"]";
+        let config = load(file, &env).unwrap();
+        assert_eq!(config.admin.api_tokens, vec!["0123456789abcdef"]);
+
+        let tokens = ("ADMIN_API_TOKENS", "0123456789abcdef, fedcba9876543210,");
+        let config = load(file, &[tokens, env[0], env[1]]).unwrap();
+        assert_eq!(
+            config.admin.api_tokens,
+            vec!["0123456789abcdef", "fedcba9876543210"]
+        );
+
+        let error = load_error("", &[("ADMIN_API_TOKENS", "secret"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "admin.api_tokens",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_telemetry() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.telemetry.otlp_endpoint, None);
+        assert_eq!(config.telemetry.service_name, "bot-monitor");
+
+        let file = "[telemetry]\notlp_endpoint = \"http://collector:4318\"\nsample_ratio = 0.1";
+        let config = load(file, &env).unwrap();
+        assert_eq!(
+            config.telemetry.otlp_endpoint.as_deref(),
+            Some("http://collector:4318")
+        );
+        assert!((config.telemetry.sample_ratio - 0.1).abs() < f64::EPSILON);
+
+        let error = load_error("", &[("TRACE_SAMPLE_RATIO", "2"), env[0], env[1]]);
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "telemetry.sample_ratio",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let env = [("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "memory")];
+
+        let config = load("", &env).unwrap();
+        assert_eq!(config.shutdown.timeout, Duration::from_secs(8));
+        assert_eq!(config.shutdown.state_file, None);
+
+        let config = load(
+            "[shutdown]\ntimeout_secs = 20",
+            &[("ORPHAN_STATE_FILE", "/data/orphans.json"), env[0], env[1]],
+        )
+        .unwrap();
+        assert_eq!(config.shutdown.timeout, Duration::from_secs(20));
+        assert_eq!(
+            config.shutdown.state_file,
+            Some(PathBuf::from("/data/orphans.json"))
+        );
+    }
+
+    #[test]
+    fn test_missing_token() {
+        let error = load_error("", &[("REGISTER_BACKEND", "memory")]);
+
+        assert!(matches!(
+            error,
+            ConfigError::Missing {
+                key: "discord.token",
+                env: "BOT_TOKEN"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_missing_table_name() {
+        let error = load_error("", &[("BOT_TOKEN", "token")]);
+
+        assert!(matches!(
+            error,
+            ConfigError::Missing {
+                key: "register.table_name",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_unknown_backend() {
+        let error = load_error("", &[("BOT_TOKEN", "token"), ("REGISTER_BACKEND", "redis")]);
+
+        assert!(matches!(
+            error,
+            ConfigError::Invalid { key: "register.backend", value, .. } if value == "redis"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        let error = load_error(
+            "",
+            &[
+                ("BOT_TOKEN", "token"),
+                ("REGISTER_BACKEND", "postgres"),
+                ("DATABASE_URL", "postgres://localhost/register"),
+                ("DATABASE_MAX_CONNECTIONS", "lots"),
+            ],
+        );
+
+        assert!(matches!(
+            error,
+            ConfigError::Invalid {
+                key: "register.max_connections",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_unknown_file_key() {
+        let file = r#"
+            [register]
+            tabel_name = "table"
+        "#;
+
+        assert!(toml::from_str::<FileConfig>(file).is_err());
+    }
+}
@@ -1,10 +1,38 @@
+use crate::domain::incidents::StatusLog;
+use crate::domain::orphans::OrphanFlags;
 use crate::domain::register::Register;
+use crate::domain::shutdown::InFlight;
+use std::time::Duration;
+
+/// When registrations whose registrant or bot the monitor can no longer reach are removed.
+#[derive(Debug, Clone, Copy)]
+pub struct CleanupPolicy {
+    /// How long an entry stays flagged before it is removed, in case the user or bot comes back.
+    pub grace_period: Duration,
+    /// Direct messages that must fail in a row before a registrant counts as unreachable.
+    pub delivery_failures: u32,
+    pub sweep_interval: Duration,
+}
+
+impl Default for CleanupPolicy {
+    fn default() -> Self {
+        Self {
+            grace_period: Duration::from_secs(7 * 24 * 60 * 60),
+            delivery_failures: 3,
+            sweep_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
 
 pub struct App<R>
 where
     R: Register,
 {
     pub register: R,
+    pub cleanup: CleanupPolicy,
+    pub orphans: OrphanFlags,
+    pub in_flight: InFlight,
+    pub statuses: StatusLog,
 }
 
 impl<R> App<R>
@@ -12,6 +40,22 @@ where
     R: Register,
 {
     pub fn new(register: R) -> Self {
-        Self { register }
+        Self {
+            register,
+            cleanup: CleanupPolicy::default(),
+            orphans: OrphanFlags::default(),
+            in_flight: InFlight::default(),
+            statuses: StatusLog::default(),
+        }
+    }
+
+    pub fn with_cleanup(mut self, cleanup: CleanupPolicy) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    pub fn with_orphans(mut self, orphans: OrphanFlags) -> Self {
+        self.orphans = orphans;
+        self
     }
 }
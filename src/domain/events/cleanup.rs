@@ -0,0 +1,482 @@
+use crate::domain::app::App;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::orphans::Orphan;
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use async_trait::async_trait;
+use std::time::{Duration, SystemTime};
+use tracing::Instrument;
+
+#[cfg(test)]
+use mockall::automock;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait CleanupEvent {
+    /// Whether the registrant or bot is still out of the monitor's reach.
+    async fn is_orphaned(&self, orphan: &Orphan) -> bool;
+    async fn bot_flagged_message(&self, entry: RegisterEntry, grace_period: Duration);
+    async fn bot_removed_message(&self, entry: RegisterEntry);
+}
+
+impl<R> App<R>
+where
+    R: Register,
+{
+    /// Starts the grace period of an orphan that has entries, telling the
+    /// registrants of a bot that is gone what will happen to it.
+    pub async fn flag_orphan<E: CleanupEvent + Sync>(&self, event: &E, orphan: Orphan) {
+        let entries = match self.orphaned_entries(&orphan).await {
+            Ok(entries) => entries,
+            Err(why) => {
+                tracing::warn!("Could not look up entries of {orphan:?} - {why:?}");
+                return;
+            }
+        };
+
+        if entries.is_empty() || !event.is_orphaned(&orphan).await {
+            return;
+        }
+
+        if !self.orphans.flag(orphan.clone(), SystemTime::now()) {
+            return;
+        }
+        tracing::info!("Flagged {orphan:?}, its entries will be removed after the grace period");
+
+        if let Orphan::BotGone(_) = orphan {
+            for entry in entries {
+                event
+                    .bot_flagged_message(entry, self.cleanup.grace_period)
+                    .await;
+            }
+        }
+    }
+
+    /// `flag_orphan` for many at once, such as everyone in a guild the monitor
+    /// left. Who has entries is looked up in one go, so only orphans that have
+    /// any cost a lookup of their own.
+    pub async fn flag_orphans<E: CleanupEvent + Sync>(&self, event: &E, orphans: Vec<Orphan>) {
+        let registered =
+            tokio::try_join!(self.register.monitored_bots(), self.register.registrants());
+        let (bots, registrants) = match registered {
+            Ok(registered) => registered,
+            Err(why) => {
+                tracing::warn!("Could not look up who has entries - {why:?}");
+                return;
+            }
+        };
+
+        for orphan in orphans {
+            let has_entries = match &orphan {
+                Orphan::BotGone(bot_id) => bots.contains(bot_id),
+                Orphan::RegistrantLeft(user_id) | Orphan::Unreachable(user_id) => {
+                    registrants.contains(user_id)
+                }
+            };
+            if has_entries {
+                self.flag_orphan(event, orphan).await;
+            }
+        }
+    }
+
+    pub fn unflag_orphan(&self, orphan: &Orphan) {
+        if self.orphans.unflag(orphan) {
+            tracing::info!("{orphan:?} is back, it is no longer flagged");
+        }
+    }
+
+    /// Counts alerts that could not be delivered, flagging registrants that
+    /// have missed too many in a row.
+    pub fn record_deliveries(&self, entries: &[RegisterEntry], undeliverable: &[String]) {
+        for entry in entries {
+            if !undeliverable.contains(&entry.user_id) {
+                self.orphans.delivered(&entry.user_id);
+                continue;
+            }
+
+            let failures = self.orphans.delivery_failed(&entry.user_id);
+            if failures >= self.cleanup.delivery_failures
+                && self.orphans.flag(
+                    Orphan::Unreachable(entry.user_id.clone()),
+                    SystemTime::now(),
+                )
+            {
+                tracing::info!(user_id = %entry.user_id, "Flagged a registrant after {failures} undeliverable alerts");
+            }
+        }
+    }
+
+    /// Removes the entries of orphans whose grace period is over and who have not come back.
+    pub async fn sweep_orphans<E: CleanupEvent + Sync>(&self, event: &E) {
+        let cutoff = SystemTime::now()
+            .checked_sub(self.cleanup.grace_period)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        for orphan in self.orphans.flagged_before(cutoff) {
+            // Only a delivered alert clears an unreachable registrant, there is
+            // nothing else to check.
+            let recheck = !matches!(orphan, Orphan::Unreachable(_));
+            if recheck && !event.is_orphaned(&orphan).await {
+                self.unflag_orphan(&orphan);
+                continue;
+            }
+
+            match self.remove_orphan(event, &orphan).await {
+                Ok(removed) => {
+                    tracing::info!("Removed {removed} entries of {orphan:?}");
+                    self.orphans.unflag(&orphan);
+                }
+                Err(why) => tracing::warn!("Failed to remove entries of {orphan:?} - {why:?}"),
+            }
+        }
+    }
+
+    /// Runs `sweep_orphans` every sweep interval, forever.
+    pub async fn run_orphan_sweeps<E: CleanupEvent + Sync>(&self, event: E) {
+        let mut interval = tokio::time::interval(self.cleanup.sweep_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes straight away, before the client has seen any guilds.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            let Some(_guard) = self.in_flight.enter() else {
+                return;
+            };
+            self.sweep_orphans(&event)
+                .instrument(tracing::info_span!("orphan_sweep"))
+                .await;
+        }
+    }
+
+    async fn orphaned_entries(&self, orphan: &Orphan) -> Result<Vec<RegisterEntry>, RegisterError> {
+        match orphan {
+            Orphan::BotGone(bot_id) => self.register.fetch(bot_id.clone()).await,
+            Orphan::RegistrantLeft(user_id) | Orphan::Unreachable(user_id) => {
+                let payload = ListEntriesPayload {
+                    user_id: user_id.clone(),
+                };
+                self.register.list(payload).await
+            }
+        }
+    }
+
+    async fn remove_orphan<E: CleanupEvent + Sync>(
+        &self,
+        event: &E,
+        orphan: &Orphan,
+    ) -> Result<usize, RegisterError> {
+        let bot_id = match orphan {
+            Orphan::RegistrantLeft(user_id) | Orphan::Unreachable(user_id) => {
+                return self.register.forget_user(user_id.clone()).await;
+            }
+            Orphan::BotGone(bot_id) => bot_id,
+        };
+
+        let mut removed = 0;
+        for entry in self.register.fetch(bot_id.clone()).await? {
+            let remove_entry = RemoveEntry {
+                user_id: entry.user_id.clone(),
+                bot_id: entry.bot_id.clone(),
+            };
+
+            match self.register.remove(remove_entry).await {
+                Ok(()) => {
+                    removed += 1;
+                    event.bot_removed_message(entry).await;
+                }
+                Err(RegisterError::NotRegistered) => {}
+                Err(why) => return Err(why),
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::app::CleanupPolicy;
+    use crate::domain::register::{MockRegister, StorageError};
+    use mockall::predicate::*;
+    use std::collections::HashSet;
+
+    fn entry(bot_id: &str, user_id: &str) -> RegisterEntry {
+        RegisterEntry {
+            bot_id: String::from(bot_id),
+            user_id: String::from(user_id),
+        }
+    }
+
+    fn app(register: MockRegister) -> App<MockRegister> {
+        App::new(register).with_cleanup(CleanupPolicy {
+            grace_period: Duration::ZERO,
+            delivery_failures: 2,
+            sweep_interval: Duration::from_secs(60),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_flag_gone_bot() {
+        let bot_id = String::from("bot_id_12345");
+        let entries = vec![
+            entry("bot_id_12345", "user_id_0"),
+            entry("bot_id_12345", "user_id_1"),
+        ];
+
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(2)
+            .with(eq(bot_id.clone()))
+            .return_const(Ok(entries));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(2).return_const(true);
+        event.expect_bot_flagged_message().times(2).return_const(());
+
+        let app = app(register);
+
+        app.flag_orphan(&event, Orphan::BotGone(bot_id.clone()))
+            .await;
+        // Flagging again does not notify again.
+        app.flag_orphan(&event, Orphan::BotGone(bot_id.clone()))
+            .await;
+        assert!(app.orphans.unflag(&Orphan::BotGone(bot_id)));
+    }
+
+    #[tokio::test]
+    async fn test_flag_not_orphaned() {
+        let mut register = MockRegister::new();
+        register
+            .expect_list()
+            .times(1)
+            .return_const(Ok(vec![entry("bot_id_12345", "user_id_12345")]));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(1).return_const(false);
+
+        let app = app(register);
+        let orphan = Orphan::RegistrantLeft(String::from("user_id_12345"));
+
+        app.flag_orphan(&event, orphan.clone()).await;
+        assert!(!app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_flag_without_entries() {
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(1)
+            .return_const(Ok(Vec::new()));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(0);
+
+        let app = app(register);
+        let orphan = Orphan::BotGone(String::from("bot_id_12345"));
+
+        app.flag_orphan(&event, orphan.clone()).await;
+        assert!(!app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_flag_orphans() {
+        let mut register = MockRegister::new();
+        register
+            .expect_monitored_bots()
+            .times(1)
+            .return_const(Ok(HashSet::from([String::from("bot_id_12345")])));
+        register
+            .expect_registrants()
+            .times(1)
+            .return_const(Ok(HashSet::from([String::from("user_id_12345")])));
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(String::from("bot_id_12345")))
+            .return_const(Ok(vec![entry("bot_id_12345", "user_id_67890")]));
+        register
+            .expect_list()
+            .times(1)
+            .withf(|payload| payload.user_id == "user_id_12345")
+            .return_const(Ok(vec![entry("bot_id_67890", "user_id_12345")]));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(2).return_const(true);
+        event.expect_bot_flagged_message().times(1).return_const(());
+
+        let app = app(register);
+        let orphans = vec![
+            Orphan::BotGone(String::from("bot_id_12345")),
+            Orphan::BotGone(String::from("bot_id_67890")),
+            Orphan::RegistrantLeft(String::from("user_id_12345")),
+            Orphan::RegistrantLeft(String::from("user_id_67890")),
+        ];
+
+        app.flag_orphans(&event, orphans.clone()).await;
+
+        let flagged: Vec<bool> = orphans
+            .iter()
+            .map(|orphan| app.orphans.unflag(orphan))
+            .collect();
+        assert_eq!(flagged, vec![true, false, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_flag_orphans_error() {
+        let mut register = MockRegister::new();
+        register
+            .expect_monitored_bots()
+            .return_const(Err(RegisterError::EntryFetchError(
+                StorageError::retryable("timed out"),
+            )));
+        register
+            .expect_registrants()
+            .return_const(Ok(HashSet::new()));
+        register.expect_fetch().times(0);
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(0);
+
+        let app = app(register);
+
+        app.flag_orphans(&event, vec![Orphan::BotGone(String::from("bot_id_12345"))])
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_record_deliveries() {
+        let app = app(MockRegister::new());
+        let entries = vec![
+            entry("bot_id_12345", "user_id_0"),
+            entry("bot_id_12345", "user_id_1"),
+        ];
+        let undeliverable = vec![String::from("user_id_0")];
+        let orphan = Orphan::Unreachable(String::from("user_id_0"));
+
+        app.record_deliveries(&entries, &undeliverable);
+        assert!(!app.orphans.unflag(&orphan));
+
+        app.record_deliveries(&entries, &undeliverable);
+        assert!(app.orphans.unflag(&orphan));
+        assert!(
+            !app.orphans
+                .unflag(&Orphan::Unreachable(String::from("user_id_1")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sweep_removes_gone_bot() {
+        let bot_id = String::from("bot_id_12345");
+        let orphan = Orphan::BotGone(bot_id.clone());
+
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(bot_id.clone()))
+            .return_const(Ok(vec![entry("bot_id_12345", "user_id_12345")]));
+        register
+            .expect_remove()
+            .times(1)
+            .with(eq(RemoveEntry {
+                user_id: String::from("user_id_12345"),
+                bot_id: bot_id.clone(),
+            }))
+            .return_const(Ok(()));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(1).return_const(true);
+        event
+            .expect_bot_removed_message()
+            .times(1)
+            .with(eq(entry("bot_id_12345", "user_id_12345")))
+            .return_const(());
+
+        let app = app(register);
+        app.orphans.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+
+        app.sweep_orphans(&event).await;
+        assert!(!app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_forgets_unreachable_registrant() {
+        let orphan = Orphan::Unreachable(String::from("user_id_12345"));
+
+        let mut register = MockRegister::new();
+        register
+            .expect_forget_user()
+            .times(1)
+            .with(eq(String::from("user_id_12345")))
+            .return_const(Ok(2));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(0);
+
+        let app = app(register);
+        app.orphans.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+
+        app.sweep_orphans(&event).await;
+        assert!(!app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_unflags_returned_registrant() {
+        let orphan = Orphan::RegistrantLeft(String::from("user_id_12345"));
+
+        let mut register = MockRegister::new();
+        register.expect_forget_user().times(0);
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(1).return_const(false);
+
+        let app = app(register);
+        app.orphans.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+
+        app.sweep_orphans(&event).await;
+        assert!(!app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_keeps_flag_on_error() {
+        let orphan = Orphan::RegistrantLeft(String::from("user_id_12345"));
+
+        let mut register = MockRegister::new();
+        register
+            .expect_forget_user()
+            .times(1)
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::retryable("error"),
+            )));
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(1).return_const(true);
+
+        let app = app(register);
+        app.orphans.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+
+        app.sweep_orphans(&event).await;
+        assert!(app.orphans.unflag(&orphan));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_waits_for_grace_period() {
+        let orphan = Orphan::RegistrantLeft(String::from("user_id_12345"));
+
+        let mut register = MockRegister::new();
+        register.expect_forget_user().times(0);
+
+        let mut event = MockCleanupEvent::new();
+        event.expect_is_orphaned().times(0);
+
+        let app = App::new(register);
+        app.orphans.flag(orphan.clone(), SystemTime::now());
+
+        app.sweep_orphans(&event).await;
+        assert!(app.orphans.unflag(&orphan));
+    }
+}
@@ -1,11 +1,12 @@
 use crate::domain::app::App;
-use crate::domain::register::Register;
+use crate::domain::register::{Register, RegisterError};
 use async_trait::async_trait;
 
 #[cfg(test)]
 use mockall::automock;
 
-#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
 pub struct CreateEntry {
     pub user_id: String,
     pub bot_id: String,
@@ -18,7 +19,9 @@ pub trait CreateEntryEvent {
     fn entry(&self) -> CreateEntry;
     fn is_bot(&self) -> bool;
     async fn not_a_bot_message(&self);
+    async fn processing_message(&self);
     async fn entry_added_message(&self);
+    async fn already_registered_message(&self);
     async fn failed_message(&self);
 }
 
@@ -32,12 +35,23 @@ where
             return;
         }
 
-        if let Err(why) = self.register.add(event.entry()).await {
-            log::warn!("Failed to add new entry - {why:?}");
-            event.failed_message().await;
-        } else {
-            log::info!("Added new entry");
-            event.entry_added_message().await;
+        event.processing_message().await;
+        let entry = event.entry();
+        let bot_id = entry.bot_id.clone();
+
+        match self.register.add(entry).await {
+            Ok(()) => {
+                tracing::info!(%bot_id, "Added new entry");
+                event.entry_added_message().await;
+            }
+            Err(RegisterError::AlreadyRegistered) => {
+                tracing::info!(%bot_id, "Entry was already registered");
+                event.already_registered_message().await;
+            }
+            Err(why) => {
+                tracing::warn!(%bot_id, "Failed to add new entry - {why:?}");
+                event.failed_message().await;
+            }
         }
     }
 }
@@ -45,7 +59,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::register::{MockRegister, RegisterError};
+    use crate::domain::register::{MockRegister, StorageError};
     use mockall::predicate::*;
 
     #[tokio::test]
@@ -67,6 +81,7 @@ mod tests {
             .return_const(Ok(()));
 
         let mut event = MockCreateEntryEvent::new();
+        event.expect_processing_message().times(1).return_const(());
         event.expect_is_bot().times(1).return_const(true);
         event.expect_entry().times(1).return_const(entry.clone());
         event.expect_entry_added_message().times(1).return_const(());
@@ -92,9 +107,12 @@ mod tests {
             .expect_add()
             .times(1)
             .with(eq(entry.clone()))
-            .return_const(Err(RegisterError::EntryCreationError));
+            .return_const(Err(RegisterError::EntryCreationError(
+                StorageError::permanent("error"),
+            )));
 
         let mut event = MockCreateEntryEvent::new();
+        event.expect_processing_message().times(1).return_const(());
         event.expect_is_bot().times(1).return_const(true);
         event.expect_entry().times(1).return_const(entry.clone());
         event.expect_failed_message().times(1).return_const(());
@@ -104,6 +122,39 @@ mod tests {
         app.add_to_register(event).await;
     }
 
+    #[tokio::test]
+    async fn test_add_to_register_already_registered() {
+        let user_id = String::from("user_id_12345");
+        let bot_id = String::from("bot_id_12345");
+
+        let entry = CreateEntry {
+            user_id,
+            bot_id,
+            version: 1,
+        };
+
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(entry.clone()))
+            .return_const(Err(RegisterError::AlreadyRegistered));
+
+        let mut event = MockCreateEntryEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_is_bot().times(1).return_const(true);
+        event.expect_entry().times(1).return_const(entry.clone());
+        event
+            .expect_already_registered_message()
+            .times(1)
+            .return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.add_to_register(event).await;
+    }
+
     #[tokio::test]
     async fn test_add_to_register_not_a_bot() {
         let mut register = MockRegister::new();
@@ -0,0 +1,344 @@
+use crate::domain::app::App;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+use mockall::automock;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+/// Whose registrations are exported.
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy)]
+pub enum ExportScope {
+    /// The bots the user registered.
+    User,
+    /// The bots in the guild anybody registered. Nothing says which guild a
+    /// registration was made in, so it goes by the bots in the guild now.
+    Guild,
+}
+
+/// A registration as it is written to, and read back from, an export file.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Serialize, Deserialize)]
+pub struct ExportedEntry {
+    pub bot_id: String,
+}
+
+/// The header row of CSV exports.
+pub const CSV_HEADER: &str = "bot_id";
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub struct ExportFile {
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+fn render(entries: Vec<RegisterEntry>, format: ExportFormat) -> Result<Vec<u8>, serde_json::Error> {
+    let rows: Vec<ExportedEntry> = entries
+        .into_iter()
+        .map(|entry| ExportedEntry {
+            bot_id: entry.bot_id,
+        })
+        .collect();
+
+    match format {
+        ExportFormat::Json => serde_json::to_vec_pretty(&rows),
+        ExportFormat::Csv => {
+            let mut csv = format!("{CSV_HEADER}\n");
+            for row in rows {
+                csv.push_str(&row.bot_id);
+                csv.push('\n');
+            }
+            Ok(csv.into_bytes())
+        }
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ExportEvent {
+    fn payload(&self) -> ListEntriesPayload;
+    fn scope(&self) -> ExportScope;
+    fn format(&self) -> ExportFormat;
+    async fn processing_message(&self);
+    /// The bots in the guild the export was asked for in, `None` if they
+    /// could not be listed.
+    async fn guild_bots(&self) -> Option<Vec<String>>;
+    async fn export_message(&self, file: ExportFile);
+    async fn empty_message(&self);
+    async fn failed_message(&self);
+}
+
+impl<R> App<R>
+where
+    R: Register,
+{
+    pub async fn export_entries<E: ExportEvent>(&self, event: E) {
+        event.processing_message().await;
+
+        let entries = match event.scope() {
+            ExportScope::User => self.register.list(event.payload()).await,
+            ExportScope::Guild => {
+                let Some(bot_ids) = event.guild_bots().await else {
+                    event.failed_message().await;
+                    return;
+                };
+                self.monitored_of(bot_ids).await
+            }
+        };
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(why) => {
+                tracing::warn!("Failed to list entries to export: {why:?}");
+                event.failed_message().await;
+                return;
+            }
+        };
+
+        if entries.is_empty() {
+            event.empty_message().await;
+            return;
+        }
+
+        let format = event.format();
+        match render(entries, format) {
+            Ok(contents) => {
+                let file = ExportFile {
+                    name: format!("registrations.{}", format.extension()),
+                    contents,
+                };
+                event.export_message(file).await;
+            }
+            Err(why) => {
+                tracing::warn!("Failed to render export: {why:?}");
+                event.failed_message().await;
+            }
+        }
+    }
+
+    /// One entry for each of the bots somebody registered.
+    async fn monitored_of(
+        &self,
+        bot_ids: Vec<String>,
+    ) -> Result<Vec<RegisterEntry>, RegisterError> {
+        let mut entries = Vec::new();
+        for bot_id in bot_ids {
+            if let Some(entry) = self.register.fetch(bot_id).await?.into_iter().next() {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+    use mockall::predicate::*;
+
+    fn entries() -> Vec<RegisterEntry> {
+        (0..2)
+            .map(|i| RegisterEntry {
+                bot_id: format!("{i}2345"),
+                user_id: String::from("user_id_12345"),
+            })
+            .collect()
+    }
+
+    fn payload() -> ListEntriesPayload {
+        ListEntriesPayload {
+            user_id: String::from("user_id_12345"),
+        }
+    }
+
+    fn user_event() -> MockExportEvent {
+        let mut event = MockExportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_scope().return_const(ExportScope::User);
+        event.expect_payload().times(1).return_const(payload());
+        event.expect_guild_bots().times(0);
+        event
+    }
+
+    #[tokio::test]
+    async fn test_export_json() {
+        let mut register = MockRegister::new();
+        register.expect_list().times(1).return_const(Ok(entries()));
+
+        let mut event = user_event();
+        event
+            .expect_format()
+            .times(1)
+            .return_const(ExportFormat::Json);
+        event
+            .expect_export_message()
+            .times(1)
+            .withf(|file| {
+                let rows: Vec<ExportedEntry> = serde_json::from_slice(&file.contents).unwrap();
+                file.name == "registrations.json"
+                    && rows
+                        == vec![
+                            ExportedEntry {
+                                bot_id: String::from("02345"),
+                            },
+                            ExportedEntry {
+                                bot_id: String::from("12345"),
+                            },
+                        ]
+            })
+            .return_const(());
+        event.expect_empty_message().times(0).return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_csv() {
+        let mut register = MockRegister::new();
+        register.expect_list().times(1).return_const(Ok(entries()));
+
+        let mut event = user_event();
+        event
+            .expect_format()
+            .times(1)
+            .return_const(ExportFormat::Csv);
+        event
+            .expect_export_message()
+            .times(1)
+            .with(eq(ExportFile {
+                name: String::from("registrations.csv"),
+                contents: b"bot_id\n02345\n12345\n".to_vec(),
+            }))
+            .return_const(());
+        event.expect_empty_message().times(0).return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_empty() {
+        let mut register = MockRegister::new();
+        register.expect_list().times(1).return_const(Ok(Vec::new()));
+
+        let mut event = user_event();
+        event.expect_export_message().times(0).return_const(());
+        event.expect_empty_message().times(1).return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_error() {
+        let mut register = MockRegister::new();
+        register
+            .expect_list()
+            .times(1)
+            .return_const(Err(RegisterError::EntryFetchError(
+                StorageError::permanent("error"),
+            )));
+
+        let mut event = user_event();
+        event.expect_export_message().times(0).return_const(());
+        event.expect_empty_message().times(0).return_const(());
+        event.expect_failed_message().times(1).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_guild() {
+        let mut register = MockRegister::new();
+        register.expect_list().times(0);
+        register
+            .expect_fetch()
+            .times(3)
+            .returning(|bot_id| match bot_id.as_str() {
+                "67890" => Ok(Vec::new()),
+                _ => Ok(vec![
+                    RegisterEntry {
+                        bot_id: bot_id.clone(),
+                        user_id: String::from("user_id_12345"),
+                    },
+                    RegisterEntry {
+                        bot_id,
+                        user_id: String::from("user_id_67890"),
+                    },
+                ]),
+            });
+
+        let mut event = MockExportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_scope().return_const(ExportScope::Guild);
+        event.expect_payload().times(0);
+        event.expect_guild_bots().times(1).return_const(Some(vec![
+            String::from("12345"),
+            String::from("67890"),
+            String::from("02345"),
+        ]));
+        event
+            .expect_format()
+            .times(1)
+            .return_const(ExportFormat::Csv);
+        event
+            .expect_export_message()
+            .times(1)
+            .with(eq(ExportFile {
+                name: String::from("registrations.csv"),
+                contents: b"bot_id\n12345\n02345\n".to_vec(),
+            }))
+            .return_const(());
+        event.expect_empty_message().times(0).return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_export_guild_bots_unknown() {
+        let mut register = MockRegister::new();
+        register.expect_fetch().times(0);
+
+        let mut event = MockExportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_scope().return_const(ExportScope::Guild);
+        event.expect_guild_bots().times(1).return_const(None);
+        event.expect_export_message().times(0).return_const(());
+        event.expect_failed_message().times(1).return_const(());
+
+        let app = App::new(register);
+
+        app.export_entries(event).await;
+    }
+}
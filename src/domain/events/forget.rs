@@ -0,0 +1,151 @@
+use crate::domain::app::App;
+use crate::domain::register::Register;
+use async_trait::async_trait;
+
+#[cfg(test)]
+use mockall::automock;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ForgetRequestEvent {
+    async fn confirmation_message(&self);
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ForgetEvent {
+    fn user_id(&self) -> String;
+    /// `flags` counts what was kept about the user while cleaning up orphans.
+    async fn forgotten_message(&self, entries: usize, flags: usize);
+    async fn failed_message(&self);
+}
+
+impl<R> App<R>
+where
+    R: Register,
+{
+    /// Asks the user to confirm before anything is deleted, see `forget_user`.
+    pub async fn request_forget<E: ForgetRequestEvent>(&self, event: E) {
+        event.confirmation_message().await;
+    }
+
+    pub async fn forget_user<E: ForgetEvent>(&self, event: E) {
+        let user_id = event.user_id();
+        match self.register.forget_user(user_id.clone()).await {
+            Ok(entries) => {
+                // Orphan flags are persisted across restarts, so they go too.
+                let flags = self.orphans.forget(&user_id);
+                tracing::info!("Forgot a user, their {entries} entries and {flags} orphan flags");
+                event.forgotten_message(entries, flags).await;
+            }
+            Err(why) => {
+                tracing::warn!("Failed to forget user - {why:?}");
+                event.failed_message().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::orphans::{Orphan, OrphanFlags};
+    use crate::domain::register::{MockRegister, RegisterError, StorageError};
+    use mockall::predicate::*;
+    use std::time::SystemTime;
+
+    #[tokio::test]
+    async fn test_request_forget() {
+        let mut register = MockRegister::new();
+        register.expect_forget_user().times(0);
+
+        let mut event = MockForgetRequestEvent::new();
+        event
+            .expect_confirmation_message()
+            .times(1)
+            .return_const(());
+
+        let app = App::new(register);
+
+        app.request_forget(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_forget_user() {
+        let user_id = String::from("user_id_12345");
+
+        let mut register = MockRegister::new();
+        register
+            .expect_forget_user()
+            .times(1)
+            .with(eq(user_id.clone()))
+            .return_const(Ok(3));
+
+        let mut event = MockForgetEvent::new();
+        event.expect_user_id().times(1).return_const(user_id);
+        event
+            .expect_forgotten_message()
+            .times(1)
+            .with(eq(3), eq(0))
+            .return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+
+        app.forget_user(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_forget_user_orphan_flags() {
+        let user_id = String::from("user_id_12345");
+
+        let mut register = MockRegister::new();
+        register.expect_forget_user().times(1).return_const(Ok(1));
+
+        let mut event = MockForgetEvent::new();
+        event
+            .expect_user_id()
+            .times(1)
+            .return_const(user_id.clone());
+        event
+            .expect_forgotten_message()
+            .times(1)
+            .with(eq(1), eq(2))
+            .return_const(());
+
+        let orphans = OrphanFlags::default();
+        orphans.flag(
+            Orphan::RegistrantLeft(user_id.clone()),
+            SystemTime::UNIX_EPOCH,
+        );
+        orphans.delivery_failed(&user_id);
+        let app = App::new(register).with_orphans(orphans);
+
+        app.forget_user(event).await;
+
+        assert_eq!(app.orphans.snapshot(), Default::default());
+    }
+
+    #[tokio::test]
+    async fn test_forget_user_error() {
+        let mut register = MockRegister::new();
+        register
+            .expect_forget_user()
+            .times(1)
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::permanent("error"),
+            )));
+
+        let mut event = MockForgetEvent::new();
+        event
+            .expect_user_id()
+            .times(1)
+            .return_const(String::from("user_id_12345"));
+        event.expect_forgotten_message().times(0).return_const(());
+        event.expect_failed_message().times(1).return_const(());
+
+        let app = App::new(register);
+
+        app.forget_user(event).await;
+    }
+}
@@ -0,0 +1,364 @@
+use crate::domain::app::App;
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::export::{CSV_HEADER, ExportFormat, ExportedEntry};
+use crate::domain::register::{Register, RegisterError};
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[cfg(test)]
+use mockall::automock;
+
+/// The most registrations a single file may import.
+pub const MAX_IMPORT_ROWS: usize = 100;
+/// Larger files are rejected before downloading them, an export of
+/// `MAX_IMPORT_ROWS` registrations is well under this.
+pub const MAX_FILE_SIZE: u32 = 64 * 1024;
+
+#[derive(Error, Debug)]
+pub enum ImportFileError {
+    #[error("the file is larger than {} KB", MAX_FILE_SIZE / 1024)]
+    TooLarge,
+    #[error("the file could not be downloaded")]
+    Download,
+    #[error("the file is not valid JSON - {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("the file is not valid UTF-8")]
+    Encoding,
+    #[error("the CSV file must start with a `{CSV_HEADER}` header")]
+    CsvHeader,
+    #[error("the file has no registrations in it")]
+    Empty,
+    #[error("the file has more than {MAX_IMPORT_ROWS} registrations in it")]
+    TooManyRows,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy)]
+pub enum ImportOutcome {
+    Added,
+    AlreadyRegistered,
+    InvalidId,
+    UnknownUser,
+    NotABot,
+    Failed,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub struct ImportResult {
+    /// 1-based position of the registration in the file, not counting any header.
+    pub row: usize,
+    pub bot_id: String,
+    pub outcome: ImportOutcome,
+}
+
+/// Reads the bot ids out of a file in the same layout `/export` writes.
+pub fn parse(contents: &[u8], format: ExportFormat) -> Result<Vec<String>, ImportFileError> {
+    let bot_ids: Vec<String> = match format {
+        ExportFormat::Json => serde_json::from_slice::<Vec<ExportedEntry>>(contents)?
+            .into_iter()
+            .map(|entry| entry.bot_id)
+            .collect(),
+        ExportFormat::Csv => {
+            let text = std::str::from_utf8(contents).map_err(|_| ImportFileError::Encoding)?;
+            let mut lines = text
+                .lines()
+                .map(|line| line.split(',').next().unwrap_or_default().trim())
+                .map(|value| value.trim_matches('"'))
+                .filter(|value| !value.is_empty());
+
+            if !lines
+                .next()
+                .is_some_and(|header| header.eq_ignore_ascii_case(CSV_HEADER))
+            {
+                return Err(ImportFileError::CsvHeader);
+            }
+
+            lines.map(String::from).collect()
+        }
+    };
+
+    if bot_ids.is_empty() {
+        Err(ImportFileError::Empty)
+    } else if bot_ids.len() > MAX_IMPORT_ROWS {
+        Err(ImportFileError::TooManyRows)
+    } else {
+        Ok(bot_ids)
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ImportEvent {
+    fn user_id(&self) -> String;
+    /// The format of the uploaded file, if it is one that can be imported.
+    fn format(&self) -> Option<ExportFormat>;
+    async fn contents(&self) -> Result<Vec<u8>, ImportFileError>;
+    /// Whether the user is a bot, `None` if there is no such user.
+    async fn is_bot(&self, bot_id: u64) -> Option<bool>;
+    async fn processing_message(&self);
+    async fn invalid_file_message(&self, reason: String);
+    async fn results_message(&self, results: Vec<ImportResult>);
+}
+
+impl<R> App<R>
+where
+    R: Register,
+{
+    pub async fn import_entries<E: ImportEvent + Sync>(&self, event: E) {
+        event.processing_message().await;
+
+        let Some(format) = event.format() else {
+            event
+                .invalid_file_message(String::from("expected a .json or .csv file"))
+                .await;
+            return;
+        };
+
+        let bot_ids = match event
+            .contents()
+            .await
+            .and_then(|contents| parse(&contents, format))
+        {
+            Ok(bot_ids) => bot_ids,
+            Err(why) => {
+                event.invalid_file_message(why.to_string()).await;
+                return;
+            }
+        };
+
+        let user_id = event.user_id();
+        let mut results = Vec::with_capacity(bot_ids.len());
+        for (i, bot_id) in bot_ids.into_iter().enumerate() {
+            let outcome = self.import_entry(&event, &user_id, &bot_id).await;
+            results.push(ImportResult {
+                row: i + 1,
+                bot_id,
+                outcome,
+            });
+        }
+
+        tracing::info!("Imported {} entries", results.len());
+        event.results_message(results).await;
+    }
+
+    async fn import_entry<E: ImportEvent + Sync>(
+        &self,
+        event: &E,
+        user_id: &str,
+        bot_id: &str,
+    ) -> ImportOutcome {
+        let Some(id) = bot_id.parse::<u64>().ok().filter(|id| *id != 0) else {
+            return ImportOutcome::InvalidId;
+        };
+
+        match event.is_bot(id).await {
+            None => return ImportOutcome::UnknownUser,
+            Some(false) => return ImportOutcome::NotABot,
+            Some(true) => {}
+        }
+
+        let entry = CreateEntry {
+            user_id: String::from(user_id),
+            bot_id: id.to_string(),
+            version: 0,
+        };
+
+        match self.register.add(entry).await {
+            Ok(()) => ImportOutcome::Added,
+            Err(RegisterError::AlreadyRegistered) => ImportOutcome::AlreadyRegistered,
+            Err(why) => {
+                tracing::warn!("Failed to import entry - {why:?}");
+                ImportOutcome::Failed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+    use mockall::predicate::*;
+
+    fn create_entry(bot_id: &str) -> CreateEntry {
+        CreateEntry {
+            user_id: String::from("user_id_12345"),
+            bot_id: String::from(bot_id),
+            version: 0,
+        }
+    }
+
+    fn event(format: ExportFormat, contents: &'static str) -> MockImportEvent {
+        let mut event = MockImportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event
+            .expect_user_id()
+            .return_const(String::from("user_id_12345"));
+        event.expect_format().return_const(Some(format));
+        event
+            .expect_contents()
+            .returning(move || Ok(contents.as_bytes().to_vec()));
+        event
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let contents = br#"[{"bot_id": "12345"}, {"bot_id": "67890"}]"#;
+
+        let return_value = parse(contents, ExportFormat::Json).unwrap();
+        assert_eq!(return_value, vec!["12345", "67890"]);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let contents = b"bot_id\r\n12345\n\"67890\"\n\n";
+
+        let return_value = parse(contents, ExportFormat::Csv).unwrap();
+        assert_eq!(return_value, vec!["12345", "67890"]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            parse(b"12345\n67890", ExportFormat::Csv),
+            Err(ImportFileError::CsvHeader)
+        ));
+        assert!(matches!(
+            parse(b"bot_id", ExportFormat::Csv),
+            Err(ImportFileError::Empty)
+        ));
+        assert!(matches!(
+            parse(b"{", ExportFormat::Json),
+            Err(ImportFileError::Json(_))
+        ));
+
+        let contents = format!("bot_id\n{}", "12345\n".repeat(MAX_IMPORT_ROWS + 1));
+        assert!(matches!(
+            parse(contents.as_bytes(), ExportFormat::Csv),
+            Err(ImportFileError::TooManyRows)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_entries() {
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(create_entry("1")))
+            .return_const(Ok(()));
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(create_entry("2")))
+            .return_const(Err(RegisterError::AlreadyRegistered));
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(create_entry("5")))
+            .return_const(Err(RegisterError::EntryCreationError(
+                StorageError::permanent("error"),
+            )));
+
+        let mut event = event(ExportFormat::Csv, "bot_id\n1\n2\nbot\n3\n4\n5");
+        event.expect_is_bot().with(eq(3)).return_const(None);
+        event.expect_is_bot().with(eq(4)).return_const(Some(false));
+        event.expect_is_bot().return_const(Some(true));
+        event
+            .expect_results_message()
+            .times(1)
+            .withf(|results| {
+                let outcomes: Vec<ImportOutcome> =
+                    results.iter().map(|result| result.outcome).collect();
+                outcomes
+                    == vec![
+                        ImportOutcome::Added,
+                        ImportOutcome::AlreadyRegistered,
+                        ImportOutcome::InvalidId,
+                        ImportOutcome::UnknownUser,
+                        ImportOutcome::NotABot,
+                        ImportOutcome::Failed,
+                    ]
+                    && results[2]
+                        == ImportResult {
+                            row: 3,
+                            bot_id: String::from("bot"),
+                            outcome: ImportOutcome::InvalidId,
+                        }
+            })
+            .return_const(());
+        event
+            .expect_invalid_file_message()
+            .times(0)
+            .return_const(());
+
+        let app = App::new(register);
+
+        app.import_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_invalid_file() {
+        let mut register = MockRegister::new();
+        register.expect_add().times(0);
+
+        let mut event = event(ExportFormat::Json, "not json");
+        event.expect_results_message().times(0).return_const(());
+        event
+            .expect_invalid_file_message()
+            .times(1)
+            .return_const(());
+
+        let app = App::new(register);
+
+        app.import_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_file_too_large() {
+        let mut register = MockRegister::new();
+        register.expect_add().times(0);
+
+        let mut event = MockImportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event
+            .expect_format()
+            .times(1)
+            .return_const(Some(ExportFormat::Csv));
+        event
+            .expect_contents()
+            .times(1)
+            .returning(|| Err(ImportFileError::TooLarge));
+        event.expect_results_message().times(0).return_const(());
+        event
+            .expect_invalid_file_message()
+            .times(1)
+            .with(eq(String::from("the file is larger than 64 KB")))
+            .return_const(());
+
+        let app = App::new(register);
+
+        app.import_entries(event).await;
+    }
+
+    #[tokio::test]
+    async fn test_import_unsupported_format() {
+        let register = MockRegister::new();
+
+        let mut event = MockImportEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_format().times(1).return_const(None);
+        event.expect_contents().times(0);
+        event.expect_results_message().times(0).return_const(());
+        event
+            .expect_invalid_file_message()
+            .times(1)
+            .with(eq(String::from("expected a .json or .csv file")))
+            .return_const(());
+
+        let app = App::new(register);
+
+        app.import_entries(event).await;
+    }
+}
@@ -5,7 +5,7 @@ use async_trait::async_trait;
 #[cfg(test)]
 use mockall::automock;
 
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub struct ListEntriesPayload {
     pub user_id: String,
 }
@@ -27,7 +27,7 @@ where
         let entries = match self.register.list(event.payload()).await {
             Ok(entries) => entries,
             Err(why) => {
-                log::warn!("Failed to list entries in register: {why:?}");
+                tracing::warn!("Failed to list entries in register: {why:?}");
                 event.failed_message().await;
                 return;
             }
@@ -45,7 +45,7 @@ where
 mod tests {
     use super::*;
     use crate::domain::app::App;
-    use crate::domain::register::{MockRegister, RegisterError};
+    use crate::domain::register::{MockRegister, RegisterError, StorageError};
     use mockall::predicate::*;
 
     #[tokio::test]
@@ -57,7 +57,9 @@ mod tests {
         register
             .expect_list()
             .times(1)
-            .return_const(Err(RegisterError::EntryFetchError));
+            .return_const(Err(RegisterError::EntryFetchError(
+                StorageError::permanent("error"),
+            )));
 
         let mut event = MockListEvent::new();
         event.expect_failed_message().times(1).return_const(());
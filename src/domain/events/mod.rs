@@ -1,5 +1,9 @@
+pub mod cleanup;
 pub mod create;
+pub mod export;
+pub mod forget;
 pub mod help;
+pub mod import;
 pub mod list;
 pub mod remove;
 pub mod status;
@@ -1,11 +1,12 @@
 use crate::domain::app::App;
-use crate::domain::register::Register;
+use crate::domain::register::{Register, RegisterError};
 use async_trait::async_trait;
 
 #[cfg(test)]
 use mockall::automock;
 
-#[cfg_attr(test, derive(Clone, Debug, PartialEq))]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
 pub struct RemoveEntry {
     pub user_id: String,
     pub bot_id: String,
@@ -15,6 +16,8 @@ pub struct RemoveEntry {
 #[async_trait]
 pub trait RemoveEvent {
     fn entry(&self) -> RemoveEntry;
+    async fn processing_message(&self);
+    async fn not_registered_message(&self);
     async fn failed_message(&self);
     async fn success_message(&self);
 }
@@ -24,12 +27,23 @@ where
     R: Register,
 {
     pub async fn remove_from_register<E: RemoveEvent>(&self, event: E) {
-        if let Err(why) = self.register.remove(event.entry()).await {
-            log::error!("Error while removing event: {why:?}");
-            event.failed_message().await;
-        } else {
-            log::info!("Successfully removed entry");
-            event.success_message().await;
+        event.processing_message().await;
+        let entry = event.entry();
+        let bot_id = entry.bot_id.clone();
+
+        match self.register.remove(entry).await {
+            Ok(()) => {
+                tracing::info!(%bot_id, "Successfully removed entry");
+                event.success_message().await;
+            }
+            Err(RegisterError::NotRegistered) => {
+                tracing::info!(%bot_id, "Tried to remove an entry that was not registered");
+                event.not_registered_message().await;
+            }
+            Err(why) => {
+                tracing::error!(%bot_id, "Error while removing event: {why:?}");
+                event.failed_message().await;
+            }
         }
     }
 }
@@ -38,7 +52,7 @@ where
 mod tests {
     use super::*;
     use crate::domain::app::App;
-    use crate::domain::register::{MockRegister, RegisterError};
+    use crate::domain::register::{MockRegister, StorageError};
     use mockall::predicate::*;
 
     #[tokio::test]
@@ -56,8 +70,13 @@ mod tests {
             .return_const(Ok(()));
 
         let mut event = MockRemoveEvent::new();
+        event.expect_processing_message().times(1).return_const(());
         event.expect_entry().times(1).return_const(entry.clone());
         event.expect_success_message().times(1).return_const(());
+        event
+            .expect_not_registered_message()
+            .times(0)
+            .return_const(());
         event.expect_failed_message().times(0).return_const(());
 
         let app = App::new(register);
@@ -76,14 +95,49 @@ mod tests {
             .expect_remove()
             .times(1)
             .with(eq(entry.clone()))
-            .return_const(Err(RegisterError::EntryRemoveError));
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::permanent("error"),
+            )));
 
         let mut event = MockRemoveEvent::new();
+        event.expect_processing_message().times(1).return_const(());
         event.expect_entry().times(1).return_const(entry.clone());
         event.expect_success_message().times(0).return_const(());
+        event
+            .expect_not_registered_message()
+            .times(0)
+            .return_const(());
         event.expect_failed_message().times(1).return_const(());
 
         let app = App::new(register);
         app.remove_from_register(event).await;
     }
+
+    #[tokio::test]
+    async fn test_remove_from_register_not_registered() {
+        let user_id = String::from("user_id_12345");
+        let bot_id = String::from("bot_id12345");
+
+        let entry = RemoveEntry { user_id, bot_id };
+
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .with(eq(entry.clone()))
+            .return_const(Err(RegisterError::NotRegistered));
+
+        let mut event = MockRemoveEvent::new();
+        event.expect_processing_message().times(1).return_const(());
+        event.expect_entry().times(1).return_const(entry.clone());
+        event.expect_success_message().times(0).return_const(());
+        event
+            .expect_not_registered_message()
+            .times(1)
+            .return_const(());
+        event.expect_failed_message().times(0).return_const(());
+
+        let app = App::new(register);
+        app.remove_from_register(event).await;
+    }
 }
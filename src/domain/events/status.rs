@@ -1,6 +1,9 @@
 use crate::domain::app::App;
+use crate::domain::incidents::BotStatus;
 use crate::domain::register::{Register, RegisterEntry};
+use crate::metrics::metrics;
 use async_trait::async_trait;
+use std::time::SystemTime;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
@@ -18,8 +21,10 @@ pub trait StatusEvent {
     fn bot_id(&self) -> String;
     fn state(&self) -> BotStates;
     async fn is_bot(&self) -> bool;
-    async fn send_offline_warning(&self, entries: Vec<RegisterEntry>);
-    async fn send_online_message(&self, entries: Vec<RegisterEntry>);
+    /// Returns the registrants the warning could never be delivered to.
+    async fn send_offline_warning(&self, entries: Vec<RegisterEntry>) -> Vec<String>;
+    /// Returns the registrants the message could never be delivered to.
+    async fn send_online_message(&self, entries: Vec<RegisterEntry>) -> Vec<String>;
 }
 
 impl<R> App<R>
@@ -27,42 +32,72 @@ where
     R: Register,
 {
     pub async fn resolve_event<E: StatusEvent>(&self, event: E) {
-        if !event.is_bot().await {
+        // Most presences are for users nobody monitors, which the cached
+        // register answers from memory, so look there before `is_bot`, which
+        // may have to ask Discord.
+        let bot_id = event.bot_id();
+        let entries = match self.register.fetch(bot_id.clone()).await {
+            Ok(entries) => entries,
+            Err(why) => {
+                tracing::error!(
+                    "Dropping status event, could not fetch register entries - {why:?}"
+                );
+                return;
+            }
+        };
+
+        if entries.is_empty() || !event.is_bot().await {
             return;
         }
+        metrics().monitored_presences.inc();
 
-        if let Some(entries) = self.register.fetch(event.bot_id()).await {
-            match event.state() {
-                BotStates::Offline => {
-                    log::info!("A bot went offline!");
-                    event.send_offline_warning(entries).await;
-                }
-                BotStates::Online => {
-                    log::info!("A bot came back online!");
-                    event.send_online_message(entries).await;
-                }
-                BotStates::NA => {}
+        let undeliverable = match event.state() {
+            BotStates::Offline => {
+                self.statuses
+                    .record(&bot_id, BotStatus::Offline, SystemTime::now());
+                tracing::info!(registrants = entries.len(), "A bot went offline!");
+                metrics().transitions.with_label_values(&["offline"]).inc();
+                event.send_offline_warning(entries.clone()).await
             }
-        }
+            BotStates::Online => {
+                self.statuses
+                    .record(&bot_id, BotStatus::Online, SystemTime::now());
+                tracing::info!(registrants = entries.len(), "A bot came back online!");
+                metrics().transitions.with_label_values(&["online"]).inc();
+                event.send_online_message(entries.clone()).await
+            }
+            BotStates::NA => return,
+        };
+
+        self.record_deliveries(&entries, &undeliverable);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::register::MockRegister;
+    use crate::domain::orphans::Orphan;
+    use crate::domain::register::{MockRegister, RegisterError, StorageError};
 
     #[tokio::test]
     async fn test_resolve_not_bot() {
         let bot_id = String::from("bot_id_12345");
+        let entry = RegisterEntry {
+            bot_id: bot_id.clone(),
+            user_id: String::from("user_id_12345"),
+        };
 
         let mut register = MockRegister::new();
-        register.expect_fetch().times(0).return_const(None);
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(bot_id.clone()))
+            .return_const(Ok(vec![entry]));
 
         let mut status_event = MockStatusEvent::new();
         status_event
             .expect_bot_id()
-            .times(0)
+            .times(1)
             .return_const(bot_id.clone());
         status_event.expect_is_bot().times(1).return_const(false);
         status_event
@@ -72,11 +107,46 @@ mod tests {
         status_event
             .expect_send_offline_warning()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
         status_event
             .expect_send_online_message()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
+
+        let app = App::new(register);
+
+        app.resolve_event(status_event).await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_unmonitored_bot() {
+        let bot_id = String::from("bot_id_12345");
+
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(bot_id.clone()))
+            .return_const(Ok(Vec::new()));
+
+        let mut status_event = MockStatusEvent::new();
+        status_event
+            .expect_bot_id()
+            .times(1)
+            .return_const(bot_id.clone());
+        status_event.expect_is_bot().times(0).return_const(true);
+        status_event
+            .expect_state()
+            .times(0)
+            .return_const(BotStates::Offline);
+        status_event
+            .expect_send_offline_warning()
+            .times(0)
+            .return_const(Vec::new());
+        status_event
+            .expect_send_online_message()
+            .times(0)
+            .return_const(Vec::new());
 
         let app = App::new(register);
 
@@ -98,7 +168,7 @@ mod tests {
             .expect_fetch()
             .times(1)
             .with(eq(bot_id.clone()))
-            .return_const(Some(entries.clone()));
+            .return_const(Ok(entries.clone()));
 
         let mut status_event = MockStatusEvent::new();
         status_event
@@ -114,11 +184,11 @@ mod tests {
             .expect_send_offline_warning()
             .times(1)
             .with(eq(entries))
-            .return_const(());
+            .return_const(Vec::new());
         status_event
             .expect_send_online_message()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
 
         let app = App::new(register);
 
@@ -140,7 +210,7 @@ mod tests {
             .expect_fetch()
             .times(1)
             .with(eq(bot_id.clone()))
-            .return_const(Some(entries.clone()));
+            .return_const(Ok(entries.clone()));
 
         let mut status_event = MockStatusEvent::new();
         status_event
@@ -155,12 +225,12 @@ mod tests {
         status_event
             .expect_send_offline_warning()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
         status_event
             .expect_send_online_message()
             .times(1)
             .with(eq(entries))
-            .return_const(());
+            .return_const(Vec::new());
 
         let app = App::new(register);
 
@@ -182,7 +252,7 @@ mod tests {
             .expect_fetch()
             .times(1)
             .with(eq(bot_id.clone()))
-            .return_const(Some(entries.clone()));
+            .return_const(Ok(entries.clone()));
 
         let mut status_event = MockStatusEvent::new();
         status_event
@@ -197,14 +267,76 @@ mod tests {
         status_event
             .expect_send_offline_warning()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
         status_event
             .expect_send_online_message()
             .times(0)
-            .return_const(());
+            .return_const(Vec::new());
 
         let app = App::new(register);
 
         app.resolve_event(status_event).await;
     }
+
+    #[tokio::test]
+    async fn test_resolve_event_fetch_error() {
+        let bot_id = String::from("bot_id_12345");
+
+        // The register retries on its own, see `Resilient`.
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(1)
+            .return_const(Err(RegisterError::EntryFetchError(
+                StorageError::retryable("throttled"),
+            )));
+
+        let mut status_event = MockStatusEvent::new();
+        status_event
+            .expect_bot_id()
+            .times(1)
+            .return_const(bot_id.clone());
+        status_event.expect_is_bot().times(0).return_const(true);
+        status_event
+            .expect_send_offline_warning()
+            .times(0)
+            .return_const(Vec::new());
+
+        let app = App::new(register);
+
+        app.resolve_event(status_event).await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_event_flags_unreachable_registrant() {
+        let bot_id = String::from("bot_id_12345");
+        let user_id = String::from("user_id_12345");
+        let entries = vec![RegisterEntry {
+            bot_id: bot_id.clone(),
+            user_id: user_id.clone(),
+        }];
+
+        let mut register = MockRegister::new();
+        register.expect_fetch().return_const(Ok(entries));
+
+        let status_event = || {
+            let mut status_event = MockStatusEvent::new();
+            status_event.expect_bot_id().return_const(bot_id.clone());
+            status_event.expect_is_bot().return_const(true);
+            status_event.expect_state().return_const(BotStates::Offline);
+            status_event
+                .expect_send_offline_warning()
+                .return_const(vec![user_id.clone()]);
+            status_event
+        };
+
+        let app = App::new(register);
+        let orphan = Orphan::Unreachable(user_id.clone());
+
+        for _ in 0..app.cleanup.delivery_failures {
+            assert!(!app.orphans.unflag(&orphan));
+            app.resolve_event(status_event()).await;
+        }
+        assert!(app.orphans.unflag(&orphan));
+    }
 }
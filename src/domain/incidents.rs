@@ -0,0 +1,136 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// How many incidents are remembered for each bot, oldest are dropped first.
+const INCIDENTS_PER_BOT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BotStatus {
+    Online,
+    Offline,
+}
+
+/// A stretch of time a monitored bot was offline, `ended_at` is `None` while it still is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incident {
+    pub started_at: SystemTime,
+    pub ended_at: Option<SystemTime>,
+}
+
+struct BotHistory {
+    /// `None` until the first status is recorded.
+    status: Option<BotStatus>,
+    since: SystemTime,
+    incidents: VecDeque<Incident>,
+}
+
+/// The last known status of every monitored bot and its recent incidents,
+/// since the monitor started.
+#[derive(Default)]
+pub struct StatusLog(Mutex<HashMap<String, BotHistory>>);
+
+impl StatusLog {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, BotHistory>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns whether the status changed, a bot's presence arrives once per
+    /// guild it is in.
+    pub fn record(&self, bot_id: &str, status: BotStatus, at: SystemTime) -> bool {
+        let mut bots = self.lock();
+        let history = bots
+            .entry(String::from(bot_id))
+            .or_insert_with(|| BotHistory {
+                status: None,
+                since: at,
+                incidents: VecDeque::new(),
+            });
+        if history.status == Some(status) {
+            return false;
+        }
+
+        match status {
+            BotStatus::Offline => {
+                if history.incidents.len() == INCIDENTS_PER_BOT {
+                    history.incidents.pop_front();
+                }
+                history.incidents.push_back(Incident {
+                    started_at: at,
+                    ended_at: None,
+                });
+            }
+            BotStatus::Online => {
+                if let Some(incident) = history.incidents.back_mut() {
+                    incident.ended_at.get_or_insert(at);
+                }
+            }
+        }
+
+        history.status = Some(status);
+        history.since = at;
+        true
+    }
+
+    /// The bot's status and when it changed to it.
+    pub fn status(&self, bot_id: &str) -> Option<(BotStatus, SystemTime)> {
+        let bots = self.lock();
+        let history = bots.get(bot_id)?;
+        history.status.map(|status| (status, history.since))
+    }
+
+    /// Newest first.
+    pub fn incidents(&self, bot_id: &str) -> Vec<Incident> {
+        self.lock()
+            .get(bot_id)
+            .map(|history| history.incidents.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_incident_opens_and_closes() {
+        let log = StatusLog::default();
+
+        assert!(log.record("bot_id_12345", BotStatus::Offline, at(10)));
+        assert!(!log.record("bot_id_12345", BotStatus::Offline, at(20)));
+        assert_eq!(
+            log.status("bot_id_12345"),
+            Some((BotStatus::Offline, at(10)))
+        );
+
+        assert!(log.record("bot_id_12345", BotStatus::Online, at(30)));
+        assert_eq!(
+            log.incidents("bot_id_12345"),
+            vec![Incident {
+                started_at: at(10),
+                ended_at: Some(at(30)),
+            }]
+        );
+        assert!(log.incidents("bot_id_67890").is_empty());
+        assert_eq!(log.status("bot_id_67890"), None);
+    }
+
+    #[test]
+    fn test_old_incidents_are_dropped() {
+        let log = StatusLog::default();
+
+        for i in 0..30 {
+            log.record("bot_id_12345", BotStatus::Offline, at(i * 2));
+            log.record("bot_id_12345", BotStatus::Online, at(i * 2 + 1));
+        }
+
+        let incidents = log.incidents("bot_id_12345");
+        assert_eq!(incidents.len(), INCIDENTS_PER_BOT);
+        assert_eq!(incidents[0].started_at, at(58));
+    }
+}
@@ -1,3 +1,6 @@
 pub mod app;
 pub mod events;
+pub mod incidents;
+pub mod orphans;
 pub mod register;
+pub mod shutdown;
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// Someone whose registrations may no longer be of use to anybody.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Orphan {
+    /// The registrant no longer shares a guild with the monitor.
+    RegistrantLeft(String),
+    /// Direct messages to the registrant keep failing.
+    Unreachable(String),
+    /// The monitored bot is no longer in any guild the monitor is in.
+    BotGone(String),
+}
+
+#[derive(Default)]
+struct Flags {
+    flagged: HashMap<Orphan, SystemTime>,
+    delivery_failures: HashMap<String, u32>,
+}
+
+/// The flags kept across restarts, so a grace period keeps running while the
+/// monitor is down.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrphanSnapshot {
+    pub flagged: Vec<(Orphan, SystemTime)>,
+    pub delivery_failures: HashMap<String, u32>,
+}
+
+/// Orphans waiting out their grace period, and how many alerts in a row each
+/// registrant has not received.
+#[derive(Default)]
+pub struct OrphanFlags(Mutex<Flags>);
+
+impl OrphanFlags {
+    fn lock(&self) -> MutexGuard<'_, Flags> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns whether the orphan was not already flagged, an orphan keeps the
+    /// time it was first flagged at.
+    pub fn flag(&self, orphan: Orphan, at: SystemTime) -> bool {
+        let mut flags = self.lock();
+        if flags.flagged.contains_key(&orphan) {
+            return false;
+        }
+
+        flags.flagged.insert(orphan, at);
+        true
+    }
+
+    pub fn unflag(&self, orphan: &Orphan) -> bool {
+        self.lock().flagged.remove(orphan).is_some()
+    }
+
+    pub fn flagged_before(&self, cutoff: SystemTime) -> Vec<Orphan> {
+        self.lock()
+            .flagged
+            .iter()
+            .filter(|(_, at)| **at <= cutoff)
+            .map(|(orphan, _)| orphan.clone())
+            .collect()
+    }
+
+    /// Returns how many messages in a row have now failed to reach the user.
+    pub fn delivery_failed(&self, user_id: &str) -> u32 {
+        let mut flags = self.lock();
+        let failures = flags
+            .delivery_failures
+            .entry(String::from(user_id))
+            .or_default();
+        *failures += 1;
+        *failures
+    }
+
+    pub fn delivered(&self, user_id: &str) {
+        let mut flags = self.lock();
+        flags.delivery_failures.remove(user_id);
+        flags
+            .flagged
+            .remove(&Orphan::Unreachable(String::from(user_id)));
+    }
+
+    /// Drops everything kept about the user, returning how many flags and
+    /// failure counts there were.
+    pub fn forget(&self, user_id: &str) -> usize {
+        let mut flags = self.lock();
+        let mut forgotten = usize::from(flags.delivery_failures.remove(user_id).is_some());
+        for orphan in [
+            Orphan::RegistrantLeft(String::from(user_id)),
+            Orphan::Unreachable(String::from(user_id)),
+        ] {
+            forgotten += usize::from(flags.flagged.remove(&orphan).is_some());
+        }
+        forgotten
+    }
+
+    pub fn snapshot(&self) -> OrphanSnapshot {
+        let flags = self.lock();
+        OrphanSnapshot {
+            flagged: flags
+                .flagged
+                .iter()
+                .map(|(orphan, at)| (orphan.clone(), *at))
+                .collect(),
+            delivery_failures: flags.delivery_failures.clone(),
+        }
+    }
+}
+
+impl From<OrphanSnapshot> for OrphanFlags {
+    fn from(snapshot: OrphanSnapshot) -> Self {
+        Self(Mutex::new(Flags {
+            flagged: snapshot.flagged.into_iter().collect(),
+            delivery_failures: snapshot.delivery_failures,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_flag_keeps_first_time() {
+        let flags = OrphanFlags::default();
+        let orphan = Orphan::BotGone(String::from("bot_id_12345"));
+        let first = SystemTime::UNIX_EPOCH;
+        let later = first + Duration::from_secs(60);
+
+        assert!(flags.flag(orphan.clone(), first));
+        assert!(!flags.flag(orphan.clone(), later));
+
+        assert_eq!(flags.flagged_before(first), vec![orphan.clone()]);
+        assert!(flags.unflag(&orphan));
+        assert!(flags.flagged_before(later).is_empty());
+    }
+
+    #[test]
+    fn test_delivered_resets_failures() {
+        let flags = OrphanFlags::default();
+        let orphan = Orphan::Unreachable(String::from("user_id_12345"));
+
+        assert_eq!(flags.delivery_failed("user_id_12345"), 1);
+        assert_eq!(flags.delivery_failed("user_id_12345"), 2);
+        flags.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+
+        flags.delivered("user_id_12345");
+        assert_eq!(flags.delivery_failed("user_id_12345"), 1);
+        assert!(!flags.unflag(&orphan));
+    }
+
+    #[test]
+    fn test_forget() {
+        let flags = OrphanFlags::default();
+        let bot = Orphan::BotGone(String::from("user_id_12345"));
+        flags.flag(
+            Orphan::RegistrantLeft(String::from("user_id_12345")),
+            SystemTime::UNIX_EPOCH,
+        );
+        flags.flag(
+            Orphan::Unreachable(String::from("user_id_12345")),
+            SystemTime::UNIX_EPOCH,
+        );
+        flags.flag(bot.clone(), SystemTime::UNIX_EPOCH);
+        flags.delivery_failed("user_id_12345");
+        flags.delivery_failed("user_id_67890");
+
+        assert_eq!(flags.forget("user_id_12345"), 3);
+        assert_eq!(flags.forget("user_id_12345"), 0);
+
+        let snapshot = flags.snapshot();
+        assert_eq!(snapshot.flagged, vec![(bot, SystemTime::UNIX_EPOCH)]);
+        assert_eq!(
+            snapshot.delivery_failures,
+            HashMap::from([(String::from("user_id_67890"), 1)])
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let flags = OrphanFlags::default();
+        let orphan = Orphan::RegistrantLeft(String::from("user_id_12345"));
+        flags.flag(orphan.clone(), SystemTime::UNIX_EPOCH);
+        flags.delivery_failed("user_id_67890");
+
+        let restored = OrphanFlags::from(flags.snapshot());
+
+        assert_eq!(restored.snapshot(), flags.snapshot());
+        assert_eq!(
+            restored.flagged_before(SystemTime::UNIX_EPOCH),
+            vec![orphan]
+        );
+        assert_eq!(restored.delivery_failed("user_id_67890"), 2);
+    }
+}
@@ -2,6 +2,10 @@ use crate::domain::events::create::CreateEntry;
 use crate::domain::events::list::ListEntriesPayload;
 use crate::domain::events::remove::RemoveEntry;
 use async_trait::async_trait;
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[cfg(test)]
@@ -14,23 +18,133 @@ pub struct RegisterEntry {
     pub user_id: String,
 }
 
-#[cfg_attr(test, derive(Clone, PartialEq))]
-#[derive(Error, Debug)]
+/// The error a register's storage failed with, and whether trying again may succeed.
+#[derive(Debug, Clone)]
+pub struct StorageError {
+    retryable: bool,
+    source: Arc<dyn StdError + Send + Sync>,
+}
+
+impl StorageError {
+    pub fn retryable(source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self {
+            retryable: true,
+            source: Arc::from(source.into()),
+        }
+    }
+
+    pub fn permanent(source: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self {
+            retryable: false,
+            source: Arc::from(source.into()),
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl StdError for StorageError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+#[cfg(test)]
+impl PartialEq for StorageError {
+    fn eq(&self, other: &Self) -> bool {
+        self.retryable == other.retryable && self.source.to_string() == other.source.to_string()
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Error, Debug, Clone)]
 #[allow(clippy::enum_variant_names)]
 pub enum RegisterError {
-    #[error("Could not create register entry")]
-    EntryCreationError,
-    #[error("Could not fetch register entry")]
-    EntryFetchError,
-    #[error("Could not remove register entry")]
-    EntryRemoveError,
+    #[error("Could not create register entry - {0}")]
+    EntryCreationError(#[source] StorageError),
+    #[error("Could not fetch register entry - {0}")]
+    EntryFetchError(#[source] StorageError),
+    #[error("Could not update register entry - {0}")]
+    EntryUpdateError(#[source] StorageError),
+    #[error("Could not remove register entry - {0}")]
+    EntryRemoveError(#[source] StorageError),
+    #[error("Register entry does not exist")]
+    NotRegistered,
+    #[error("Register entry already exists")]
+    AlreadyRegistered,
+}
+
+impl RegisterError {
+    /// Whether the storage was only temporarily unavailable, so the same call may succeed later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::EntryCreationError(why)
+            | Self::EntryFetchError(why)
+            | Self::EntryUpdateError(why)
+            | Self::EntryRemoveError(why) => why.is_retryable(),
+            Self::NotRegistered | Self::AlreadyRegistered => false,
+        }
+    }
 }
 
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait Register {
-    async fn fetch(&self, bot_id: String) -> Option<Vec<RegisterEntry>>;
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError>;
     async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError>;
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError>;
     async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError>;
     async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError>;
+    /// Removes every entry the user has, returning how many there were.
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError>;
+    /// Every bot at least one user is registered for.
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError>;
+    /// Every user registered for at least one bot.
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError>;
+}
+
+#[async_trait]
+impl<T> Register for Box<T>
+where
+    T: Register + Send + Sync + ?Sized,
+{
+    async fn fetch(&self, bot_id: String) -> Result<Vec<RegisterEntry>, RegisterError> {
+        (**self).fetch(bot_id).await
+    }
+
+    async fn add(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        (**self).add(entry).await
+    }
+
+    async fn update(&self, entry: CreateEntry) -> Result<(), RegisterError> {
+        (**self).update(entry).await
+    }
+
+    async fn remove(&self, entry: RemoveEntry) -> Result<(), RegisterError> {
+        (**self).remove(entry).await
+    }
+
+    async fn list(&self, entry: ListEntriesPayload) -> Result<Vec<RegisterEntry>, RegisterError> {
+        (**self).list(entry).await
+    }
+
+    async fn forget_user(&self, user_id: String) -> Result<usize, RegisterError> {
+        (**self).forget_user(user_id).await
+    }
+
+    async fn monitored_bots(&self) -> Result<HashSet<String>, RegisterError> {
+        (**self).monitored_bots().await
+    }
+
+    async fn registrants(&self) -> Result<HashSet<String>, RegisterError> {
+        (**self).registrants().await
+    }
 }
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Counts the events being handled, so shutdown can stop new ones from
+/// starting and wait for the running ones to finish.
+#[derive(Default)]
+pub struct InFlight {
+    closed: AtomicBool,
+    running: AtomicUsize,
+    idle: Notify,
+}
+
+/// Keeps its event counted as in flight until dropped.
+pub struct InFlightGuard<'a>(&'a InFlight);
+
+impl InFlight {
+    /// Returns `None` once shutdown has started.
+    pub fn enter(&self) -> Option<InFlightGuard<'_>> {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        if self.closed.load(Ordering::SeqCst) {
+            self.exit();
+            return None;
+        }
+
+        Some(InFlightGuard(self))
+    }
+
+    fn exit(&self) {
+        if self.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify_waiters();
+        }
+    }
+
+    /// Stops new events from being handled and waits up to `timeout` for the
+    /// running ones, returning whether they all finished.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.closed.store(true, Ordering::SeqCst);
+
+        let finished = async {
+            loop {
+                // Created before the check so a wake up in between is not missed.
+                let idle = self.idle.notified();
+                if self.running.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, finished).await.is_ok()
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[tokio::test]
+    async fn test_drain_when_idle() {
+        let in_flight = InFlight::default();
+
+        assert!(in_flight.drain(TIMEOUT).await);
+        assert!(in_flight.enter().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_running_events() {
+        let in_flight = Arc::new(InFlight::default());
+
+        let running = in_flight.clone();
+        let handle = tokio::spawn(async move {
+            let _guard = running.enter().unwrap();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        });
+        tokio::task::yield_now().await;
+
+        assert!(in_flight.drain(TIMEOUT).await);
+        assert!(handle.is_finished());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_times_out() {
+        let in_flight = InFlight::default();
+        let _guard = in_flight.enter().unwrap();
+
+        assert!(!in_flight.drain(TIMEOUT).await);
+    }
+}
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+static HEALTH: LazyLock<Health> = LazyLock::new(Health::default);
+
+/// What `/readyz` reports on, updated by the gateway and the register as they go.
+pub struct Health {
+    gateway_ready: AtomicBool,
+    register_ok: AtomicBool,
+    /// Whether each shard this process runs is connected.
+    shards: Mutex<HashMap<u32, bool>>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Readiness {
+    pub gateway: bool,
+    pub shards: bool,
+    pub register: bool,
+}
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.gateway && self.shards && self.register
+    }
+}
+
+pub fn health() -> &'static Health {
+    &HEALTH
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            gateway_ready: AtomicBool::new(false),
+            // The register was reachable at startup, or the bot would not have started.
+            register_ok: AtomicBool::new(true),
+            shards: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Health {
+    pub fn gateway_ready(&self) {
+        self.gateway_ready.store(true, Ordering::SeqCst);
+    }
+
+    pub fn shard_connected(&self, shard_id: u32, connected: bool) {
+        self.shards
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(shard_id, connected);
+    }
+
+    pub fn register_call(&self, succeeded: bool) {
+        self.register_ok.store(succeeded, Ordering::SeqCst);
+    }
+
+    pub fn readiness(&self) -> Readiness {
+        let shards = self.shards.lock().unwrap_or_else(|e| e.into_inner());
+
+        Readiness {
+            gateway: self.gateway_ready.load(Ordering::SeqCst),
+            shards: !shards.is_empty() && shards.values().all(|connected| *connected),
+            register: self.register_ok.load(Ordering::SeqCst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness() {
+        let health = Health::default();
+        assert!(!health.readiness().is_ready());
+
+        health.gateway_ready();
+        health.shard_connected(0, true);
+        health.shard_connected(1, true);
+        assert!(health.readiness().is_ready());
+
+        health.shard_connected(1, false);
+        assert!(!health.readiness().shards);
+
+        health.shard_connected(1, true);
+        health.register_call(false);
+        assert_eq!(
+            health.readiness(),
+            Readiness {
+                gateway: true,
+                shards: true,
+                register: false
+            }
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use crate::config::{LogFormat, TelemetryConfig};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// The spans exported as traces, whatever `RUST_LOG` is set to: the bot's own
+/// events and register calls, Discord HTTP requests and DynamoDB operations.
+const TRACE_FILTER: &str = "register_bot=info,serenity::http=info,aws_sdk_dynamodb=debug";
+
+/// Flushes exported traces when the bot stops.
+pub struct Telemetry(Option<SdkTracerProvider>);
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        if let Some(provider) = self.0
+            && let Err(why) = provider.shutdown()
+        {
+            tracing::warn!("Could not flush traces - {why}");
+        }
+    }
+}
+
+/// Logs to stdout, filtered by `RUST_LOG`, and exports traces over OTLP when
+/// an endpoint is configured. Records from crates still using `log` are picked
+/// up too.
+pub fn init(format: LogFormat, telemetry: Option<&TelemetryConfig>) -> Telemetry {
+    let logs = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    };
+
+    let provider = telemetry
+        .filter(|telemetry| telemetry.otlp_endpoint.is_some())
+        .map(tracer_provider);
+    let traces = match &provider {
+        Some(Ok(provider)) => Some(
+            tracing_opentelemetry::layer()
+                .with_tracer(provider.tracer("register-bot"))
+                .with_filter(EnvFilter::new(TRACE_FILTER)),
+        ),
+        _ => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(logs.with_filter(EnvFilter::from_default_env()))
+        .with(traces)
+        .init();
+
+    match provider {
+        Some(Ok(provider)) => Telemetry(Some(provider)),
+        Some(Err(why)) => {
+            tracing::error!("Could not set up trace export, traces will not be exported - {why}");
+            Telemetry(None)
+        }
+        None => Telemetry(None),
+    }
+}
+
+fn tracer_provider(
+    telemetry: &TelemetryConfig,
+) -> Result<SdkTracerProvider, opentelemetry_otlp::ExporterBuildError> {
+    let endpoint = telemetry.otlp_endpoint.as_deref().unwrap_or_default();
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            telemetry.sample_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(telemetry.service_name.clone())
+                .build(),
+        )
+        .build())
+}
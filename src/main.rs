@@ -1,20 +1,188 @@
-use crate::adapters::register::init_register;
+use crate::adapters::orphans::{load_orphans, save_orphans};
+use crate::adapters::register::{init_register, init_uncached_register, migrate};
+use crate::config::{Config, LogFormat};
 use crate::domain::app::App;
+use crate::ports::cli::{self, Cli, Command, describe_config};
 use crate::ports::clients::Client;
 use crate::ports::clients::init_client;
+use crate::ports::http::admin;
+use crate::ports::http::probe::probe;
+use clap::Parser;
 use dotenv::dotenv;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::net::TcpListener;
 
 mod adapters;
+mod config;
 mod domain;
+mod health;
+mod logging;
+mod metrics;
 pub mod ports;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
     dotenv().ok();
-    env_logger::init();
-    let register = init_register().await;
-    let app = App::new(register);
-    let mut client = init_client(app).await;
+    let config = Config::load();
+    // Only the bot itself exports traces, not one-off commands.
+    let runs_bot = !cli.healthcheck && cli.command.is_none();
+    // An invalid config still gets its error logged, in the default format.
+    let telemetry = match &config {
+        Ok(config) => logging::init(config.log_format, runs_bot.then_some(&config.telemetry)),
+        Err(_) => logging::init(LogFormat::default(), None),
+    };
+    let config = match config {
+        Ok(config) => config,
+        Err(why) => {
+            tracing::error!("Invalid configuration - {why}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    client.run().await;
+    if cli.healthcheck {
+        return healthcheck(&config).await;
+    }
+    if let Some(command) = cli.command {
+        return run_command(&config, command).await;
+    }
+
+    // Bind before connecting anything, so a taken port fails fast.
+    let listener = match config.http.listen_addr {
+        Some(addr) => match TcpListener::bind(addr).await {
+            Ok(listener) => Some(listener),
+            Err(why) => {
+                tracing::error!("Could not listen on {addr} - {why}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+    if listener.is_none() && !config.admin.api_tokens.is_empty() {
+        tracing::warn!("ADMIN_API_TOKENS is set but HTTP_LISTEN_ADDR is not, the admin API is off");
+    }
+
+    let register = match init_register(&config.register).await {
+        Ok(register) => register,
+        Err(why) => {
+            tracing::error!("Could not open the register - {why}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut app = App::new(register).with_cleanup(config.cleanup);
+    if let Some(path) = &config.shutdown.state_file {
+        app = app.with_orphans(load_orphans(path));
+    }
+    let app = Arc::new(app);
+
+    if let Some(listener) = listener {
+        let admin = (!config.admin.api_tokens.is_empty())
+            .then(|| admin::router(app.clone(), config.admin.api_tokens.clone()));
+        if let Ok(addr) = listener.local_addr() {
+            let api = if admin.is_some() {
+                " and the admin API"
+            } else {
+                ""
+            };
+            tracing::info!("Serving metrics{api} on {addr}");
+        }
+        tokio::spawn(ports::http::serve(listener, admin));
+    }
+    let mut client = match init_client(app.clone(), &config.discord, config.shutdown.timeout).await
+    {
+        Ok(client) => client,
+        Err(why) => {
+            tracing::error!("{why}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut exit_code = ExitCode::SUCCESS;
+    if let Err(why) = client.run(shutdown_signal()).await {
+        tracing::error!("{why}");
+        exit_code = ExitCode::FAILURE;
+    }
+
+    if let Some(path) = &config.shutdown.state_file
+        && let Err(why) = save_orphans(&app.orphans, path)
+    {
+        tracing::error!("Could not save flagged orphans to {path:?} - {why}");
+        exit_code = ExitCode::FAILURE;
+    }
+
+    tracing::info!("Shut down");
+    telemetry.shutdown();
+    exit_code
+}
+
+async fn run_command(config: &Config, command: Command) -> ExitCode {
+    let result = match command {
+        Command::CheckConfig => {
+            describe_config(config, &mut std::io::stdout()).map_err(|why| why.to_string())
+        }
+        Command::Migrate => match migrate(&config.register.backend).await {
+            Ok(()) => {
+                println!("Register is up to date");
+                Ok(())
+            }
+            Err(why) => Err(format!("Could not migrate the register - {why}")),
+        },
+        Command::Registrations { command } => {
+            match init_uncached_register(&config.register).await {
+                Ok(register) => cli::registrations(&register, command, &mut std::io::stdout())
+                    .await
+                    .map_err(|why| why.to_string()),
+                Err(why) => Err(format!("Could not open the register - {why}")),
+            }
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(why) => {
+            tracing::error!("{why}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Probes `/readyz` on a running bot, for a container HEALTHCHECK.
+async fn healthcheck(config: &Config) -> ExitCode {
+    let Some(addr) = config.http.listen_addr else {
+        tracing::error!("The healthcheck needs HTTP_LISTEN_ADDR to be set");
+        return ExitCode::FAILURE;
+    };
+
+    match probe(addr, "/readyz").await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(why) => {
+            tracing::error!("Healthcheck failed - {why}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[allow(clippy::missing_panics_doc)]
+async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Error listening for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Error listening for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => tracing::info!("Received SIGINT"),
+        () = terminate => tracing::info!("Received SIGTERM"),
+    }
 }
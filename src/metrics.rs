@@ -0,0 +1,125 @@
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+    pub presence_updates: IntCounter,
+    /// Presences of bots somebody has registered.
+    pub monitored_presences: IntCounter,
+    pub transitions: IntCounterVec,
+    pub notifications: IntCounterVec,
+    pub register_latency: HistogramVec,
+    pub register_errors: IntCounterVec,
+    /// 0 while the breaker is closed, 1 while it is open and 2 while half open.
+    pub register_breaker_state: IntGauge,
+    pub commands: IntCounterVec,
+}
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    #[allow(clippy::missing_panics_doc)]
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some(String::from("bot_monitor")), None)
+            .expect("Error creating metrics registry");
+
+        let metrics = Self {
+            presence_updates: IntCounter::new(
+                "presence_updates_total",
+                "Presence updates received from the gateway",
+            )
+            .expect("Error creating metric"),
+            monitored_presences: IntCounter::new(
+                "monitored_presences_total",
+                "Presence updates for registered bots",
+            )
+            .expect("Error creating metric"),
+            transitions: IntCounterVec::new(
+                Opts::new(
+                    "transitions_total",
+                    "Registered bots going offline or online",
+                ),
+                &["state"],
+            )
+            .expect("Error creating metric"),
+            notifications: IntCounterVec::new(
+                Opts::new("notifications_total", "Notifications sent to registrants"),
+                &["transport", "outcome"],
+            )
+            .expect("Error creating metric"),
+            register_latency: HistogramVec::new(
+                HistogramOpts::new(
+                    "register_call_duration_seconds",
+                    "How long calls to the register take",
+                ),
+                &["operation"],
+            )
+            .expect("Error creating metric"),
+            register_errors: IntCounterVec::new(
+                Opts::new("register_errors_total", "Register calls that failed"),
+                &["operation"],
+            )
+            .expect("Error creating metric"),
+            register_breaker_state: IntGauge::new(
+                "register_circuit_breaker_state",
+                "Register circuit breaker state, 0 closed, 1 open, 2 half open",
+            )
+            .expect("Error creating metric"),
+            commands: IntCounterVec::new(
+                Opts::new("commands_total", "Slash commands invoked"),
+                &["name"],
+            )
+            .expect("Error creating metric"),
+            registry,
+        };
+
+        for collector in [
+            Box::new(metrics.presence_updates.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(metrics.monitored_presences.clone()),
+            Box::new(metrics.transitions.clone()),
+            Box::new(metrics.notifications.clone()),
+            Box::new(metrics.register_latency.clone()),
+            Box::new(metrics.register_errors.clone()),
+            Box::new(metrics.register_breaker_state.clone()),
+            Box::new(metrics.commands.clone()),
+        ] {
+            metrics
+                .registry
+                .register(collector)
+                .expect("Error registering metric");
+        }
+
+        metrics
+    }
+
+    /// Every metric in the Prometheus text format.
+    pub fn encode(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap_or_else(|why| {
+                tracing::error!("Could not encode metrics - {why}");
+                String::new()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        metrics().commands.with_label_values(&["help"]).inc();
+
+        let encoded = metrics().encode();
+
+        assert!(encoded.contains("bot_monitor_commands_total{name=\"help\"}"));
+        assert!(encoded.contains("# TYPE bot_monitor_presence_updates_total counter"));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::config::{Config, RegisterBackend, Sharding};
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use clap::{Args, Parser, Subcommand};
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// Monitors Discord bots and alerts the users who registered them when they
+/// go offline. Runs the bot when no command is given.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// Probe `/readyz` on a running bot and exit non-zero if it is not ready.
+    #[arg(long)]
+    pub healthcheck: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect and fix registrations in the configured register.
+    Registrations {
+        #[command(subcommand)]
+        command: RegistrationsCommand,
+    },
+    /// Load and validate the configuration, then exit.
+    CheckConfig,
+    /// Bring the register's schema up to date, then exit.
+    Migrate,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RegistrationsCommand {
+    /// List a user's or a bot's registrations.
+    List(ListArgs),
+    /// Register a user to be alerted about a bot.
+    Add(EntryArgs),
+    /// Remove a user's registration for a bot.
+    Remove(EntryArgs),
+}
+
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct ListArgs {
+    /// The Discord ID of the user whose registrations are listed.
+    #[arg(long)]
+    user: Option<u64>,
+    /// The Discord ID of the bot whose registrations are listed.
+    #[arg(long)]
+    bot: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct EntryArgs {
+    /// The Discord ID of the registered user.
+    #[arg(long)]
+    user: u64,
+    /// The Discord ID of the monitored bot.
+    #[arg(long)]
+    bot: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error(transparent)]
+    Register(#[from] RegisterError),
+    #[error("Could not write output - {0}")]
+    Output(#[from] io::Error),
+}
+
+/// Runs a registrations command against the register, printing what it did to `out`.
+pub async fn registrations<R>(
+    register: &R,
+    command: RegistrationsCommand,
+    out: &mut impl Write,
+) -> Result<(), CliError>
+where
+    R: Register,
+{
+    match command {
+        RegistrationsCommand::List(ListArgs {
+            user: Some(user), ..
+        }) => {
+            let payload = ListEntriesPayload {
+                user_id: user.to_string(),
+            };
+            print_entries(out, &register.list(payload).await?)
+        }
+        RegistrationsCommand::List(ListArgs { bot: Some(bot), .. }) => {
+            print_entries(out, &register.fetch(bot.to_string()).await?)
+        }
+        // clap requires one of them.
+        RegistrationsCommand::List(_) => Ok(()),
+        RegistrationsCommand::Add(EntryArgs { user, bot }) => {
+            let entry = CreateEntry {
+                user_id: user.to_string(),
+                bot_id: bot.to_string(),
+                version: 0,
+            };
+            match register.add(entry).await {
+                Ok(()) => writeln!(out, "Registered user {user} for bot {bot}")?,
+                Err(RegisterError::AlreadyRegistered) => {
+                    writeln!(out, "User {user} is already registered for bot {bot}")?;
+                }
+                Err(why) => return Err(why.into()),
+            }
+            Ok(())
+        }
+        RegistrationsCommand::Remove(EntryArgs { user, bot }) => {
+            let entry = RemoveEntry {
+                user_id: user.to_string(),
+                bot_id: bot.to_string(),
+            };
+            register.remove(entry).await?;
+            writeln!(out, "Removed user {user}'s registration for bot {bot}")?;
+            Ok(())
+        }
+    }
+}
+
+fn print_entries(out: &mut impl Write, entries: &[RegisterEntry]) -> Result<(), CliError> {
+    if entries.is_empty() {
+        writeln!(out, "No registrations")?;
+    }
+    for entry in entries {
+        writeln!(out, "user {}\tbot {}", entry.user_id, entry.bot_id)?;
+    }
+    Ok(())
+}
+
+/// Summarises a config that loaded, leaving out tokens and connection strings.
+pub fn describe_config(config: &Config, out: &mut impl Write) -> io::Result<()> {
+    let backend = match &config.register.backend {
+        RegisterBackend::DynamoDB { table_name, .. } => format!("dynamodb, table {table_name}"),
+        RegisterBackend::SQLite { .. } => String::from("sqlite"),
+        RegisterBackend::Postgres { .. } => String::from("postgres"),
+        RegisterBackend::Memory => String::from("memory"),
+    };
+    let sharding = match config.discord.sharding {
+        Sharding::Single => String::from("single"),
+        Sharding::Auto => String::from("auto"),
+        Sharding::Range { first, last, total } => {
+            format!("shards {first} to {last} of {total}")
+        }
+    };
+    let http = config
+        .http
+        .listen_addr
+        .map_or_else(|| String::from("off"), |addr| addr.to_string());
+
+    writeln!(out, "Configuration is valid")?;
+    writeln!(out, "Register backend: {backend}")?;
+    writeln!(out, "Sharding: {sharding}")?;
+    writeln!(out, "HTTP server: {http}")?;
+    writeln!(out, "Admin API tokens: {}", config.admin.api_tokens.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+    use clap::CommandFactory;
+    use mockall::predicate::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("register-bot").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+
+        let cli = parse(&[]).unwrap();
+        assert!(cli.command.is_none() && !cli.healthcheck);
+        assert!(parse(&["--healthcheck"]).unwrap().healthcheck);
+        assert!(matches!(
+            parse(&["check-config"]).unwrap().command,
+            Some(Command::CheckConfig)
+        ));
+
+        assert!(parse(&["registrations", "list"]).is_err());
+        assert!(parse(&["registrations", "list", "--user", "1", "--bot", "2"]).is_err());
+        assert!(parse(&["registrations", "add", "--user", "abc", "--bot", "2"]).is_err());
+    }
+
+    async fn run(register: MockRegister, args: &[&str]) -> (Result<(), CliError>, String) {
+        let Some(Command::Registrations { command }) = parse(args).unwrap().command else {
+            panic!("expected a registrations command");
+        };
+        let mut out = Vec::new();
+        let result = registrations(&register, command, &mut out).await;
+
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_by_bot() {
+        let mut register = MockRegister::new();
+        register
+            .expect_fetch()
+            .times(1)
+            .with(eq(String::from("2")))
+            .return_const(Ok(vec![RegisterEntry {
+                bot_id: String::from("2"),
+                user_id: String::from("1"),
+            }]));
+
+        let (result, out) = run(register, &["registrations", "list", "--bot", "2"]).await;
+
+        assert!(result.is_ok());
+        assert_eq!(out, "user 1\tbot 2\n");
+    }
+
+    #[tokio::test]
+    async fn test_add() {
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(CreateEntry {
+                user_id: String::from("1"),
+                bot_id: String::from("2"),
+                version: 0,
+            }))
+            .return_const(Err(RegisterError::AlreadyRegistered));
+
+        let (result, out) = run(
+            register,
+            &["registrations", "add", "--user", "1", "--bot", "2"],
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(out, "User 1 is already registered for bot 2\n");
+    }
+
+    #[tokio::test]
+    async fn test_remove_failed() {
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::permanent("denied"),
+            )));
+
+        let (result, out) = run(
+            register,
+            &["registrations", "remove", "--user", "1", "--bot", "2"],
+        )
+        .await;
+
+        assert!(matches!(result, Err(CliError::Register(_))));
+        assert!(out.is_empty());
+    }
+}
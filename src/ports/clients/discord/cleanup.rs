@@ -0,0 +1,191 @@
+use crate::domain::events::cleanup::CleanupEvent;
+use crate::domain::orphans::Orphan;
+use crate::domain::register::RegisterEntry;
+use crate::ports::clients::discord::event::direct_message;
+use async_trait::async_trait;
+use serenity::Client as SerenityClient;
+use serenity::all::{
+    Cache, Context, GuildId, GuildPagination, Http, HttpError, MessageBuilder, UserId,
+};
+use serenity::prelude::{TypeMap, TypeMapKey};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Discord's error code for "Unknown Member".
+const UNKNOWN_MEMBER: isize = 10007;
+/// The most guilds Discord lists in one page.
+const GUILD_PAGE_SIZE: u64 = 200;
+/// The most guilds whose membership one check looks up over HTTP. A user who
+/// could only be checked in more than this counts as sharing a guild.
+const MAX_MEMBER_LOOKUPS: usize = 5;
+
+/// Whether this process runs every gateway shard, and so has every guild in its cache.
+pub struct RunsEveryShard;
+
+impl TypeMapKey for RunsEveryShard {
+    type Value = bool;
+}
+
+pub struct DiscordCleanupEvent {
+    cache: Arc<Cache>,
+    http: Arc<Http>,
+    every_shard: bool,
+}
+
+impl DiscordCleanupEvent {
+    async fn new(cache: Arc<Cache>, http: Arc<Http>, data: &Arc<RwLock<TypeMap>>) -> Self {
+        let every_shard = data
+            .read()
+            .await
+            .get::<RunsEveryShard>()
+            .copied()
+            .unwrap_or(true);
+
+        Self {
+            cache,
+            http,
+            every_shard,
+        }
+    }
+
+    pub async fn from_client(client: &SerenityClient) -> Self {
+        Self::new(client.cache.clone(), client.http.clone(), &client.data).await
+    }
+
+    pub async fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx.cache.clone(), ctx.http.clone(), &ctx.data).await
+    }
+
+    /// Every guild the monitor is in, `None` if they cannot all be known.
+    async fn guilds(&self) -> Option<Vec<GuildId>> {
+        if self.every_shard {
+            let guilds = self.cache.guilds();
+            if guilds.is_empty() || self.cache.unavailable_guilds().len() > 0 {
+                return None;
+            }
+            return Some(guilds);
+        }
+
+        // Other processes hold the guilds on the other shards, so only Discord knows them all.
+        let mut guilds = Vec::new();
+        loop {
+            let after = guilds.last().copied().map(GuildPagination::After);
+            let page = match self.http.get_guilds(after, Some(GUILD_PAGE_SIZE)).await {
+                Ok(page) => page,
+                Err(why) => {
+                    tracing::warn!("Could not list guilds - {why:?}");
+                    return None;
+                }
+            };
+
+            let full = page.len() as u64 == GUILD_PAGE_SIZE;
+            guilds.extend(page.into_iter().map(|guild| guild.id));
+            if !full {
+                break;
+            }
+        }
+
+        (!guilds.is_empty()).then_some(guilds)
+    }
+
+    /// Whether the user is in any guild the monitor is in. Anything that
+    /// cannot be checked counts as shared, so nobody is removed by mistake.
+    async fn shares_a_guild(&self, user_id: UserId) -> bool {
+        // Guilds whose members are all cached answer without any HTTP call.
+        let mut complete = HashSet::new();
+        for guild_id in self.cache.guilds() {
+            let Some(guild) = self.cache.guild(guild_id) else {
+                continue;
+            };
+            if guild.members.contains_key(&user_id) {
+                return true;
+            }
+            if guild.members.len() as u64 >= guild.member_count {
+                complete.insert(guild_id);
+            }
+        }
+
+        let Some(guilds) = self.guilds().await else {
+            return true;
+        };
+        let unchecked: Vec<GuildId> = guilds
+            .into_iter()
+            .filter(|guild_id| !complete.contains(guild_id))
+            .collect();
+        if unchecked.len() > MAX_MEMBER_LOOKUPS {
+            tracing::debug!(
+                "Membership is not cached for {} guilds, not checking them",
+                unchecked.len()
+            );
+            return true;
+        }
+
+        for guild_id in unchecked {
+            match self.http.get_member(guild_id, user_id).await {
+                Ok(_) => return true,
+                Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+                    if response.error.code == UNKNOWN_MEMBER => {}
+                Err(why) => {
+                    tracing::warn!("Could not check guild membership - {why:?}");
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    async fn message_registrant(&self, entry: &RegisterEntry, message: String) {
+        let Ok(user_id) = UserId::from_str(&entry.user_id) else {
+            return;
+        };
+
+        direct_message(&self.http, user_id, message).await;
+    }
+}
+
+#[async_trait]
+impl CleanupEvent for DiscordCleanupEvent {
+    async fn is_orphaned(&self, orphan: &Orphan) -> bool {
+        match orphan {
+            Orphan::RegistrantLeft(user_id) | Orphan::BotGone(user_id) => {
+                let Ok(user_id) = UserId::from_str(user_id) else {
+                    return false;
+                };
+                !self.shares_a_guild(user_id).await
+            }
+            Orphan::Unreachable(_) => true,
+        }
+    }
+
+    async fn bot_flagged_message(&self, entry: RegisterEntry, grace_period: Duration) {
+        let Ok(bot_id) = UserId::from_str(&entry.bot_id) else {
+            return;
+        };
+        let days = grace_period.as_secs().div_ceil(24 * 60 * 60);
+
+        let message = MessageBuilder::new()
+            .mention(&bot_id)
+            .push(" is no longer in any server I am in. If it does not come back within ")
+            .push(format!(
+                "{days} day(s) I will remove it from your register."
+            ))
+            .build();
+        self.message_registrant(&entry, message).await;
+    }
+
+    async fn bot_removed_message(&self, entry: RegisterEntry) {
+        let Ok(bot_id) = UserId::from_str(&entry.bot_id) else {
+            return;
+        };
+
+        let message = MessageBuilder::new()
+            .mention(&bot_id)
+            .push(" has not come back to any server I am in, so I removed it from your register.")
+            .build();
+        self.message_registrant(&entry, message).await;
+    }
+}
@@ -1,40 +1,113 @@
+use crate::config::{DiscordConfig, Sharding};
 use crate::domain::app::App;
+use crate::domain::orphans::Orphan;
 use crate::domain::register::Register;
-use crate::ports::clients::Client;
-use crate::ports::clients::discord::commands::add::DiscordCreateEvent;
-use crate::ports::clients::discord::commands::help::DiscordHelpEvent;
-use crate::ports::clients::discord::commands::list::DiscordListEvent;
-use crate::ports::clients::discord::commands::remove::RemoveDiscordEvent;
-use crate::ports::clients::discord::commands::{add, help, list, remove};
+use crate::health::health;
+use crate::metrics::metrics;
+use crate::ports::clients::discord::cleanup::{DiscordCleanupEvent, RunsEveryShard};
+use crate::ports::clients::discord::commands::forget::{
+    self, CANCEL_BUTTON_ID, CONFIRM_BUTTON_ID, DiscordForgetEvent,
+};
+use crate::ports::clients::discord::commands::sync::{CommandsGuild, sync_commands};
+use crate::ports::clients::discord::commands::{Commands, Registry};
 use crate::ports::clients::discord::event::DiscordStatusEvent;
+use crate::ports::clients::{Client, ClientError};
 use async_trait::async_trait;
 use serenity::Client as SerenityClient;
-use serenity::all::{Command, Context, GatewayIntents, Interaction, Presence, Ready};
+use serenity::all::{
+    ChunkGuildFilter, CommandInteraction, ComponentInteraction, ConnectionStage, Context,
+    GatewayIntents, Guild, GuildId, Interaction, Member, Presence, Ready, ShardStageUpdateEvent,
+    UnavailableGuild, User,
+};
 use serenity::client::EventHandler;
-use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
 
-pub struct DiscordClient(SerenityClient);
+pub struct DiscordClient<R>
+where
+    R: Register,
+{
+    client: SerenityClient,
+    app: Arc<App<R>>,
+    sharding: Sharding,
+    shutdown_timeout: Duration,
+}
 
-impl DiscordClient {
-    #[allow(clippy::missing_panics_doc)]
-    pub async fn new<R: Register + Send + Sync + 'static>(app: App<R>) -> Self {
-        let token = env::var("BOT_TOKEN").expect("Bot token wasn't in env vars");
-        let intents = GatewayIntents::DIRECT_MESSAGES | GatewayIntents::GUILD_PRESENCES;
+impl<R> DiscordClient<R>
+where
+    R: Register + Send + Sync + 'static,
+{
+    pub async fn new(
+        app: Arc<App<R>>,
+        config: &DiscordConfig,
+        shutdown_timeout: Duration,
+    ) -> Result<Self, ClientError> {
+        let intents = GatewayIntents::DIRECT_MESSAGES
+            | GatewayIntents::GUILD_PRESENCES
+            | GatewayIntents::GUILDS
+            | GatewayIntents::GUILD_MEMBERS;
 
-        let client = SerenityClient::builder(&token, intents)
-            .event_handler(app)
+        let client = SerenityClient::builder(&config.token, intents)
+            .event_handler_arc(app.clone())
+            .type_map_insert::<RunsEveryShard>(!matches!(config.sharding, Sharding::Range { .. }))
+            .type_map_insert::<CommandsGuild>(config.commands_guild.map(GuildId::new))
+            .type_map_insert::<Registry<R>>(Arc::new(Commands::new()))
             .await
-            .expect("Error creating client");
+            .map_err(ClientError::Create)?;
+
+        let cleanup = DiscordCleanupEvent::from_client(&client).await;
+        let sweeps = app.clone();
+        tokio::spawn(async move { sweeps.run_orphan_sweeps(cleanup).await });
 
-        Self(client)
+        Ok(Self {
+            client,
+            app,
+            sharding: config.sharding,
+            shutdown_timeout,
+        })
     }
 }
 
 #[async_trait]
-impl Client for DiscordClient {
-    async fn run(&mut self) {
-        if let Err(why) = self.0.start().await {
-            println!("Failed to start DiscordClient - {why:?}");
+impl<R> Client for DiscordClient<R>
+where
+    R: Register + Send + Sync + 'static,
+{
+    async fn run<S>(&mut self, shutdown: S) -> Result<(), ClientError>
+    where
+        S: Future<Output = ()> + Send + 'static,
+    {
+        let shard_manager = self.client.shard_manager.clone();
+        let app = self.app.clone();
+        let timeout = self.shutdown_timeout;
+
+        let stopped = async move {
+            shutdown.await;
+            tracing::info!("Shutting down, waiting up to {timeout:?} for in flight events");
+            if !app.in_flight.drain(timeout).await {
+                tracing::warn!("Timed out waiting for in flight events, shutting down anyway");
+            }
+            shard_manager.shutdown_all().await;
+        };
+
+        let started = async {
+            match self.sharding {
+                Sharding::Single => self.client.start().await,
+                Sharding::Auto => self.client.start_autosharded().await,
+                // Serenity runs the end of the range too.
+                Sharding::Range { first, last, total } => {
+                    self.client.start_shard_range(first..last, total).await
+                }
+            }
+        };
+
+        // `shutdown_all` only makes `start` return once a shard is running, so
+        // stop waiting on it when a shard is still trying to connect.
+        tokio::select! {
+            started = started => started.map_err(ClientError::Start),
+            () = stopped => Ok(()),
         }
     }
 }
@@ -42,67 +115,197 @@ impl Client for DiscordClient {
 #[async_trait]
 impl<R> EventHandler for App<R>
 where
-    R: Register + Send + Sync,
+    R: Register + Send + Sync + 'static,
 {
     async fn presence_update(&self, ctx: Context, presence: Presence) {
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        metrics().presence_updates.inc();
+        let span = span_in_guild(
+            tracing::info_span!("presence_update", bot_id = %presence.user.id, guild_id = Empty),
+            presence.guild_id,
+        );
+
         let event = DiscordStatusEvent::new(ctx, presence);
-        self.resolve_event(event).await;
+        self.resolve_event(event).instrument(span).await;
+    }
+
+    async fn guild_member_addition(&self, _: Context, member: Member) {
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        let _span = tracing::info_span!(
+            "guild_member_addition",
+            user_id = %member.user.id,
+            guild_id = %member.guild_id
+        )
+        .entered();
+
+        let user_id = member.user.id.to_string();
+        self.unflag_orphan(&Orphan::BotGone(user_id.clone()));
+        self.unflag_orphan(&Orphan::RegistrantLeft(user_id));
     }
-    async fn ready(&self, ctx: Context, _: Ready) {
-        if let Err(err) = Command::create_global_command(&ctx, add::register()).await {
-            log::warn!("Could not create command {err:?}");
-        } else {
-            log::info!("Created add command");
-        }
 
-        if let Err(err) = Command::create_global_command(&ctx, remove::register()).await {
-            log::warn!("Could not create command {err:?}");
-        } else {
-            log::info!("Created remove command");
+    async fn guild_create(&self, ctx: Context, guild: Guild, _: Option<bool>) {
+        // Large guilds arrive without their members, have them all sent so
+        // orphan checks can be answered from the cache.
+        if (guild.members.len() as u64) < guild.member_count {
+            ctx.shard
+                .chunk_guild(guild.id, None, false, ChunkGuildFilter::None, None);
         }
+    }
+
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        _: Option<Member>,
+    ) {
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        let span =
+            tracing::info_span!("guild_member_removal", user_id = %user.id, guild_id = %guild_id);
+
+        let event = DiscordCleanupEvent::from_context(&ctx).await;
+        self.flag_orphan(&event, orphan(&user))
+            .instrument(span)
+            .await;
+    }
 
-        if let Err(err) = Command::create_global_command(&ctx, list::register()).await {
-            log::warn!("Could not create command {err:?}");
-        } else {
-            log::info!("Created list command");
+    async fn guild_delete(&self, ctx: Context, incomplete: UnavailableGuild, full: Option<Guild>) {
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        // An unavailable guild is an outage, the monitor is still in it.
+        if incomplete.unavailable {
+            return;
         }
 
-        if let Err(err) = Command::create_global_command(&ctx, help::register()).await {
-            log::warn!("Could not create command {err:?}");
-        } else {
-            log::info!("Created help command");
+        let Some(guild) = full else {
+            return;
+        };
+        let span = tracing::info_span!("guild_delete", guild_id = %guild.id);
+
+        let event = DiscordCleanupEvent::from_context(&ctx).await;
+        let orphans = guild
+            .members
+            .values()
+            .map(|member| orphan(&member.user))
+            .collect();
+        self.flag_orphans(&event, orphans).instrument(span).await;
+    }
+
+    async fn ready(&self, ctx: Context, _: Ready) {
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        health().shard_connected(ctx.shard_id.0, true);
+        health().gateway_ready();
+
+        // Commands are per application, so one shard is enough to sync them.
+        if ctx.shard_id.0 == 0 {
+            let data = ctx.data.read().await;
+            let guild = data.get::<CommandsGuild>().copied().flatten();
+            let definitions = data
+                .get::<Registry<R>>()
+                .map(|commands| commands.definitions())
+                .unwrap_or_default();
+            drop(data);
+            sync_commands(&ctx.http, guild, &definitions).await;
         }
 
-        log::info!("Bot is ready");
+        tracing::info!("Bot is ready");
+    }
+
+    async fn shard_stage_update(&self, _: Context, event: ShardStageUpdateEvent) {
+        health().shard_connected(event.shard_id.0, event.new == ConnectionStage::Connected);
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            if command.user.bot {
-                return;
+        let Some(_guard) = self.in_flight.enter() else {
+            return;
+        };
+        match interaction {
+            Interaction::Command(command) => {
+                if command.user.bot {
+                    return;
+                }
+                metrics()
+                    .commands
+                    .with_label_values(&[command.data.name.as_str()])
+                    .inc();
+
+                let span = span_in_guild(
+                    tracing::info_span!(
+                        "command",
+                        command = %command.data.name,
+                        user_id = %command.user.id,
+                        guild_id = Empty
+                    ),
+                    command.guild_id,
+                );
+                self.run_command(ctx, command).instrument(span).await;
             }
+            Interaction::Component(component) => {
+                let span = span_in_guild(
+                    tracing::info_span!(
+                        "component",
+                        custom_id = %component.data.custom_id,
+                        user_id = %component.user.id,
+                        guild_id = Empty
+                    ),
+                    component.guild_id,
+                );
+                self.run_component(ctx, component).instrument(span).await;
+            }
+            _ => {}
+        }
+    }
+}
 
-            match command.data.name.as_str() {
-                "add" => {
-                    if let Some(event) = DiscordCreateEvent::new(ctx, command) {
-                        self.add_to_register(event).await;
-                    }
-                }
-                "remove" => {
-                    if let Some(event) = RemoveDiscordEvent::new(ctx, command) {
-                        self.remove_from_register(event).await;
-                    }
-                }
-                "list" => {
-                    let event = DiscordListEvent::new(ctx, command);
-                    self.list_entries(event).await;
-                }
-                "help" => {
-                    let event = DiscordHelpEvent::new(ctx, command);
-                    self.send_help_message(event).await;
-                }
-                _ => {}
+impl<R> App<R>
+where
+    R: Register + Send + Sync + 'static,
+{
+    async fn run_command(&self, ctx: Context, command: CommandInteraction) {
+        let commands = ctx.data.read().await.get::<Registry<R>>().cloned();
+        if let Some(handler) = commands
+            .as_deref()
+            .and_then(|commands| commands.get(&command.data.name))
+        {
+            handler.run(self, ctx, command).await;
+        }
+    }
+
+    async fn run_component(&self, ctx: Context, component: ComponentInteraction) {
+        match component.data.custom_id.as_str() {
+            CONFIRM_BUTTON_ID => {
+                let event = DiscordForgetEvent::new(ctx, component);
+                self.forget_user(event).await;
             }
+            CANCEL_BUTTON_ID => forget::cancelled_message(&ctx, &component).await,
+            _ => {}
         }
     }
 }
+
+/// Adds the guild to a span, events from DMs have none.
+fn span_in_guild(span: Span, guild_id: Option<GuildId>) -> Span {
+    if let Some(guild_id) = guild_id {
+        span.record("guild_id", tracing::field::display(guild_id));
+    }
+    span
+}
+
+/// What a user leaving the monitor's guilds would leave behind, depending on
+/// whether it is a monitored bot or a registrant.
+fn orphan(user: &User) -> Orphan {
+    if user.bot {
+        Orphan::BotGone(user.id.to_string())
+    } else {
+        Orphan::RegistrantLeft(user.id.to_string())
+    }
+}
@@ -1,23 +1,53 @@
+use crate::domain::app::App;
 use crate::domain::events::create::{CreateEntry, CreateEntryEvent};
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
 use crate::ports::clients::discord::utils::messages;
 use async_trait::async_trait;
 use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    Permissions, ResolvedValue, User,
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, Permissions,
+    ResolvedValue, User,
 };
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("add")
-        .description("Add a bot to a register")
-        .add_option(
+pub struct AddCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for AddCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "add"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add a bot to a register"
+    }
+
+    fn help(&self) -> &'static str {
+        "Add the specified bot to a register for monitoring."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
             CreateCommandOption::new(
                 CommandOptionType::User,
                 "bot",
                 "The bot you want to add to the register",
             )
             .required(true),
-        )
-        .default_member_permissions(Permissions::ADMINISTRATOR)
+        ]
+    }
+
+    fn permissions(&self) -> Option<Permissions> {
+        Some(Permissions::ADMINISTRATOR)
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        if let Some(event) = DiscordCreateEvent::new(ctx, command) {
+            app.add_to_register(event).await;
+        }
+    }
 }
 
 pub struct DiscordCreateEvent {
@@ -74,15 +104,24 @@ impl CreateEntryEvent for DiscordCreateEvent {
         .await;
     }
 
+    async fn processing_message(&self) {
+        messages::defer_ephemeral(&self.ctx, &self.command).await;
+    }
+
     async fn entry_added_message(&self) {
         let message = format!(
             "Added to {} to the register. I will now DM you when it goes offline and when it comes online.",
             self.bot.name
         );
-        messages::send_ephemeral(&self.ctx, &self.command, &message).await;
+        messages::edit_response(&self.ctx, &self.command, &message).await;
+    }
+
+    async fn already_registered_message(&self) {
+        let message = format!("{} is already in your register.", self.bot.name);
+        messages::edit_response(&self.ctx, &self.command, &message).await;
     }
 
     async fn failed_message(&self) {
-        messages::send_ephemeral(&self.ctx, &self.command, "Failed to add bot to register").await;
+        messages::edit_response(&self.ctx, &self.command, "Failed to add bot to register").await;
     }
 }
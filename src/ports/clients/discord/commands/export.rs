@@ -0,0 +1,168 @@
+use crate::domain::app::App;
+use crate::domain::events::export::{ExportEvent, ExportFile, ExportFormat, ExportScope};
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
+use crate::ports::clients::discord::utils::messages;
+use async_trait::async_trait;
+use serenity::all::{
+    CommandInteraction, CommandOptionType, Context, CreateAttachment, CreateCommandOption,
+    Permissions, ResolvedValue, UserId,
+};
+
+/// The most members Discord lists in one request.
+const MEMBERS_PER_PAGE: u64 = 1000;
+
+pub struct ExportCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for ExportCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "export"
+    }
+
+    fn description(&self) -> &'static str {
+        "Download the bots in your register, or monitored in this server, as a file"
+    }
+
+    fn help(&self) -> &'static str {
+        "Download your register, or every monitored bot in this server, as a JSON or CSV file."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "format",
+                "The format of the file, defaults to JSON",
+            )
+            .add_string_choice("JSON", "json")
+            .add_string_choice("CSV", "csv"),
+            CreateCommandOption::new(
+                CommandOptionType::String,
+                "scope",
+                "Whose bots to export, defaults to yours",
+            )
+            .add_string_choice("Mine", "user")
+            .add_string_choice("This server", "guild"),
+        ]
+    }
+
+    fn permissions(&self) -> Option<Permissions> {
+        Some(Permissions::ADMINISTRATOR)
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        let event = DiscordExportEvent::new(ctx, command);
+        app.export_entries(event).await;
+    }
+}
+
+pub struct DiscordExportEvent {
+    ctx: Context,
+    command: CommandInteraction,
+    format: ExportFormat,
+    scope: ExportScope,
+}
+
+impl DiscordExportEvent {
+    pub fn new(ctx: Context, command: CommandInteraction) -> Self {
+        let mut format = ExportFormat::Json;
+        let mut scope = ExportScope::User;
+
+        for option in command.data.options() {
+            match (option.name, option.value) {
+                ("format", ResolvedValue::String("csv")) => format = ExportFormat::Csv,
+                ("scope", ResolvedValue::String("guild")) => scope = ExportScope::Guild,
+                _ => {}
+            }
+        }
+
+        Self {
+            ctx,
+            command,
+            format,
+            scope,
+        }
+    }
+}
+
+#[async_trait]
+impl ExportEvent for DiscordExportEvent {
+    fn payload(&self) -> ListEntriesPayload {
+        ListEntriesPayload {
+            user_id: self.command.user.id.to_string(),
+        }
+    }
+
+    fn scope(&self) -> ExportScope {
+        self.scope
+    }
+
+    fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    async fn processing_message(&self) {
+        messages::defer_ephemeral(&self.ctx, &self.command).await;
+    }
+
+    async fn guild_bots(&self) -> Option<Vec<String>> {
+        let guild_id = self.command.guild_id?;
+        let mut bots = Vec::new();
+        let mut after: Option<UserId> = None;
+
+        loop {
+            let members = guild_id
+                .members(&self.ctx.http, Some(MEMBERS_PER_PAGE), after)
+                .await
+                .inspect_err(|why| tracing::warn!("Failed to list guild members: {why:?}"))
+                .ok()?;
+
+            after = members.last().map(|member| member.user.id);
+            bots.extend(
+                members
+                    .iter()
+                    .filter(|member| member.user.bot)
+                    .map(|member| member.user.id.to_string()),
+            );
+
+            if members.len() < MEMBERS_PER_PAGE as usize {
+                return Some(bots);
+            }
+        }
+    }
+
+    async fn export_message(&self, file: ExportFile) {
+        let attachment = CreateAttachment::bytes(file.contents, file.name);
+        let message = match self.scope {
+            ExportScope::User => "Here is your register, use /import to load it again.",
+            ExportScope::Guild => {
+                "Here are the monitored bots in this server, use /import to add them to your register."
+            }
+        };
+        messages::edit_response_with_file(&self.ctx, &self.command, message, attachment).await;
+    }
+
+    async fn empty_message(&self) {
+        let message = match self.scope {
+            ExportScope::User => "You have no bots in your register to export",
+            ExportScope::Guild => "Nobody monitors a bot in this server",
+        };
+        messages::edit_response(&self.ctx, &self.command, message).await;
+    }
+
+    async fn failed_message(&self) {
+        let message = match self.scope {
+            ExportScope::User => "Failed to export your register :(",
+            ExportScope::Guild if self.command.guild_id.is_none() => {
+                "Use this in a server to export the bots monitored there"
+            }
+            ExportScope::Guild => "Failed to export the bots monitored in this server :(",
+        };
+        messages::edit_response(&self.ctx, &self.command, message).await;
+    }
+}
@@ -0,0 +1,109 @@
+use crate::domain::app::App;
+use crate::domain::events::forget::{ForgetEvent, ForgetRequestEvent};
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
+use crate::ports::clients::discord::utils::messages;
+use async_trait::async_trait;
+use serenity::all::{ButtonStyle, CommandInteraction, ComponentInteraction, Context, CreateButton};
+
+pub const CONFIRM_BUTTON_ID: &str = "forget-me:confirm";
+pub const CANCEL_BUTTON_ID: &str = "forget-me:cancel";
+
+pub struct ForgetCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for ForgetCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "forget-me"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete everything I have stored about you"
+    }
+
+    fn help(&self) -> &'static str {
+        "Delete everything I have stored about you, after asking you to confirm."
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        let event = DiscordForgetRequestEvent::new(ctx, command);
+        app.request_forget(event).await;
+    }
+}
+
+pub struct DiscordForgetRequestEvent {
+    ctx: Context,
+    command: CommandInteraction,
+}
+
+impl DiscordForgetRequestEvent {
+    pub fn new(ctx: Context, command: CommandInteraction) -> Self {
+        Self { ctx, command }
+    }
+}
+
+#[async_trait]
+impl ForgetRequestEvent for DiscordForgetRequestEvent {
+    async fn confirmation_message(&self) {
+        let buttons = vec![
+            CreateButton::new(CONFIRM_BUTTON_ID)
+                .label("Delete my data")
+                .style(ButtonStyle::Danger),
+            CreateButton::new(CANCEL_BUTTON_ID)
+                .label("Cancel")
+                .style(ButtonStyle::Secondary),
+        ];
+        messages::send_ephemeral_with_buttons(
+            &self.ctx,
+            &self.command,
+            "This removes every bot you are monitoring and cannot be undone. Are you sure?",
+            buttons,
+        )
+        .await;
+    }
+}
+
+pub struct DiscordForgetEvent {
+    ctx: Context,
+    component: ComponentInteraction,
+}
+
+impl DiscordForgetEvent {
+    pub fn new(ctx: Context, component: ComponentInteraction) -> Self {
+        Self { ctx, component }
+    }
+}
+
+#[async_trait]
+impl ForgetEvent for DiscordForgetEvent {
+    fn user_id(&self) -> String {
+        self.component.user.id.to_string()
+    }
+
+    async fn forgotten_message(&self, entries: usize, flags: usize) {
+        let flags = match flags {
+            0 => String::new(),
+            flags => format!(", {flags} pending cleanup record(s)"),
+        };
+        let message = format!(
+            "Done. I deleted the {entries} bot(s) you were monitoring{flags} and no longer hold anything about you."
+        );
+        messages::update_component_message(&self.ctx, &self.component, &message).await;
+    }
+
+    async fn failed_message(&self) {
+        messages::update_component_message(
+            &self.ctx,
+            &self.component,
+            "Failed to delete your data, please try again",
+        )
+        .await;
+    }
+}
+
+pub async fn cancelled_message(ctx: &Context, component: &ComponentInteraction) {
+    messages::update_component_message(ctx, component, "Cancelled, nothing was deleted.").await;
+}
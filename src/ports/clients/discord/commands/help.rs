@@ -1,12 +1,12 @@
+use crate::domain::app::App;
 use crate::domain::events::help::HelpEvent;
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
 use crate::ports::clients::discord::utils::messages;
 use async_trait::async_trait;
-use serenity::all::{CommandInteraction, Context, CreateCommand};
+use serenity::all::{CommandInteraction, Context, Permissions};
 
-const HELP_MESSAGE: &str =
-    "
- ```ansi
-🕵️ \u{001b}[1;10;4;31mI am Monitor Bot\u{001b}[0m
+const HEADER: &str = "🕵️ \u{001b}[1;10;4;31mI am Monitor Bot\u{001b}[0m
 
 I am a Discord bot that monitors other bots and alerts you when they go offline. Perfect for server admins who rely on multiple bots and want to ensure maximum uptime.
 
@@ -14,38 +14,122 @@ I will send you a Direct Message when a monitored bot goes offline.
 
 To function properly, I use the following privileged intents:
 - `GUILD_PRESENCES`
+- `GUILD_MEMBERS`
 - `DIRECT_MESSAGES`
 
-\u{001b}[1;10;4;31mAll Commands:\u{001b}[0m
-\u{001b}[1;34m/add\u{001b}[0m - Add the specified bot to a register for monitoring. (Requires you to be an administrator)
-\u{001b}[1;34m/remove\u{001b}[0m - Removes the specified bot from the register. (Requires you to be an administrator)
-\u{001b}[1;34m/list\u{001b}[0m - Lists all current warnings you have registered (Requires you to be an administrator)
-\u{001b}[1;34m/help\u{001b}[0m - Show this message.
+\u{001b}[1;10;4;31mAll Commands:\u{001b}[0m";
 
+const FOOTER: &str =
+    "\u{001b}[1;10;4;31mHaving issues with me or have suggestions for how I can improve?\u{001b}[0m
+Please raise a ticket here https://github.com/i-h-bar/bot-monitor/issues";
 
-\u{001b}[1;10;4;31mHaving issues with me or have suggestions for how I can improve?\u{001b}[0m
-Please raise a ticket here https://github.com/i-h-bar/bot-monitor/issues
-```
-";
+const NAME: &str = "help";
+const HELP: &str = "Show this message.";
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("help").description("Shows the help message")
+/// Lists every command with its help text, the message is built once from the
+/// commands it is given and itself.
+pub struct HelpCommand {
+    message: String,
+}
+
+impl HelpCommand {
+    pub fn new<R>(commands: &[Box<dyn SlashCommand<R>>]) -> Self
+    where
+        R: Register + Send + Sync,
+    {
+        let mut lines: Vec<String> = commands
+            .iter()
+            .map(|command| line(command.name(), command.help(), command.permissions()))
+            .collect();
+        lines.push(line(NAME, HELP, None));
+
+        Self {
+            message: format!(
+                "\n ```ansi\n{HEADER}\n{}\n\n\n{FOOTER}\n```\n",
+                lines.join("\n")
+            ),
+        }
+    }
+}
+
+fn line(name: &str, help: &str, permissions: Option<Permissions>) -> String {
+    let requires = match permissions {
+        Some(Permissions::ADMINISTRATOR) => String::from(" (Requires you to be an administrator)"),
+        Some(permissions) => format!(" (Requires the {permissions} permissions)"),
+        None => String::new(),
+    };
+
+    format!("\u{001b}[1;34m/{name}\u{001b}[0m - {help}{requires}")
+}
+
+#[async_trait]
+impl<R> SlashCommand<R> for HelpCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows the help message"
+    }
+
+    fn help(&self) -> &'static str {
+        HELP
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        let event = DiscordHelpEvent::new(ctx, command, self.message.clone());
+        app.send_help_message(event).await;
+    }
 }
 
 pub struct DiscordHelpEvent {
     ctx: Context,
     command: CommandInteraction,
+    message: String,
 }
 
 impl DiscordHelpEvent {
-    pub fn new(ctx: Context, command: CommandInteraction) -> Self {
-        DiscordHelpEvent { ctx, command }
+    pub fn new(ctx: Context, command: CommandInteraction, message: String) -> Self {
+        DiscordHelpEvent {
+            ctx,
+            command,
+            message,
+        }
     }
 }
 
 #[async_trait]
 impl HelpEvent for DiscordHelpEvent {
     async fn send_message(&self) {
-        messages::send_ephemeral(&self.ctx, &self.command, HELP_MESSAGE).await;
+        messages::send_ephemeral(&self.ctx, &self.command, &self.message).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::MockRegister;
+    use crate::ports::clients::discord::commands::add::AddCommand;
+    use crate::ports::clients::discord::commands::forget::ForgetCommand;
+
+    #[test]
+    fn test_help_lists_commands() {
+        let commands: Vec<Box<dyn SlashCommand<MockRegister>>> =
+            vec![Box::new(AddCommand), Box::new(ForgetCommand)];
+
+        let message = HelpCommand::new(&commands).message;
+
+        let listed = [
+            "\u{001b}[1;34m/add\u{001b}[0m - Add the specified bot to a register for monitoring. (Requires you to be an administrator)",
+            "\u{001b}[1;34m/forget-me\u{001b}[0m - Delete everything I have stored about you, after asking you to confirm.",
+            "\u{001b}[1;34m/help\u{001b}[0m - Show this message.",
+        ]
+        .join("\n");
+        assert!(message.contains(&format!("All Commands:\u{001b}[0m\n{listed}\n\n\n")));
+        assert!(message.starts_with("\n ```ansi\n"));
+        assert!(message.ends_with("\n```\n"));
     }
 }
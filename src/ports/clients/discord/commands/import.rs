@@ -0,0 +1,161 @@
+use crate::domain::app::App;
+use crate::domain::events::export::ExportFormat;
+use crate::domain::events::import::{
+    ImportEvent, ImportFileError, ImportOutcome, ImportResult, MAX_FILE_SIZE,
+};
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
+use crate::ports::clients::discord::utils::messages;
+use crate::ports::clients::discord::utils::user::user_from_id;
+use async_trait::async_trait;
+use serenity::all::{
+    Attachment, CommandInteraction, CommandOptionType, Context, CreateCommandOption, Permissions,
+    ResolvedValue,
+};
+
+/// Discord's limit on the length of a message.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+pub struct ImportCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for ImportCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "import"
+    }
+
+    fn description(&self) -> &'static str {
+        "Add the bots in a file made by /export to your register"
+    }
+
+    fn help(&self) -> &'static str {
+        "Add the bots in a file made by /export to your register."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
+            CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                "file",
+                "A .json or .csv file made by /export",
+            )
+            .required(true),
+        ]
+    }
+
+    fn permissions(&self) -> Option<Permissions> {
+        Some(Permissions::ADMINISTRATOR)
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        if let Some(event) = DiscordImportEvent::new(ctx, command) {
+            app.import_entries(event).await;
+        }
+    }
+}
+
+pub struct DiscordImportEvent {
+    ctx: Context,
+    command: CommandInteraction,
+    file: Attachment,
+}
+
+impl DiscordImportEvent {
+    pub fn new(ctx: Context, command: CommandInteraction) -> Option<Self> {
+        let mut file: Option<Attachment> = None;
+
+        for option in command.data.options() {
+            if option.name == "file"
+                && let ResolvedValue::Attachment(attachment) = option.value
+            {
+                file = Some(attachment.clone());
+            }
+        }
+
+        Some(Self {
+            ctx,
+            command,
+            file: file?,
+        })
+    }
+}
+
+fn describe(outcome: ImportOutcome) -> &'static str {
+    match outcome {
+        ImportOutcome::Added => "added",
+        ImportOutcome::AlreadyRegistered => "already in your register",
+        ImportOutcome::InvalidId => "not a valid user id",
+        ImportOutcome::UnknownUser => "no such user",
+        ImportOutcome::NotABot => "not a bot",
+        ImportOutcome::Failed => "failed, try again later",
+    }
+}
+
+#[async_trait]
+impl ImportEvent for DiscordImportEvent {
+    fn user_id(&self) -> String {
+        self.command.user.id.to_string()
+    }
+
+    fn format(&self) -> Option<ExportFormat> {
+        let (_, extension) = self.file.filename.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    async fn contents(&self) -> Result<Vec<u8>, ImportFileError> {
+        if self.file.size > MAX_FILE_SIZE {
+            return Err(ImportFileError::TooLarge);
+        }
+
+        self.file.download().await.map_err(|why| {
+            tracing::warn!("Failed to download import file: {why:?}");
+            ImportFileError::Download
+        })
+    }
+
+    async fn is_bot(&self, bot_id: u64) -> Option<bool> {
+        user_from_id(&self.ctx, bot_id).await.map(|user| user.bot)
+    }
+
+    async fn processing_message(&self) {
+        messages::defer_ephemeral(&self.ctx, &self.command).await;
+    }
+
+    async fn invalid_file_message(&self, reason: String) {
+        let message = format!("Could not import that file, {reason}.");
+        messages::edit_response(&self.ctx, &self.command, &message).await;
+    }
+
+    async fn results_message(&self, results: Vec<ImportResult>) {
+        let added = results
+            .iter()
+            .filter(|result| matches!(result.outcome, ImportOutcome::Added))
+            .count();
+        let mut message = format!("Imported {added} of {} bots:", results.len());
+
+        for (i, result) in results.iter().enumerate() {
+            let line = format!(
+                "\nRow {}: `{}` {}",
+                result.row,
+                result.bot_id,
+                describe(result.outcome)
+            );
+            let more = format!("\n...and {} more", results.len() - i);
+
+            if message.len() + line.len() + more.len() > MAX_MESSAGE_LENGTH {
+                message.push_str(&more);
+                break;
+            }
+            message.push_str(&line);
+        }
+
+        messages::edit_response(&self.ctx, &self.command, &message).await;
+    }
+}
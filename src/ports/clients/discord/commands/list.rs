@@ -1,18 +1,42 @@
+use crate::domain::app::App;
 use crate::domain::events::list::{ListEntriesPayload, ListEvent};
+use crate::domain::register::Register;
 use crate::domain::register::RegisterEntry;
+use crate::ports::clients::discord::commands::SlashCommand;
 use crate::ports::clients::discord::utils::messages;
 use crate::ports::clients::discord::utils::user::user_from_id;
 use async_trait::async_trait;
-use serenity::all::{
-    CommandInteraction, Context, CreateCommand, MessageBuilder, Permissions, User,
-};
+use serenity::all::{CommandInteraction, Context, MessageBuilder, Permissions, User};
 use serenity::futures::future::join_all;
 use std::str::FromStr;
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("list")
-        .description("List all the warnings you currently have active")
-        .default_member_permissions(Permissions::ADMINISTRATOR)
+pub struct ListCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for ListCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "list"
+    }
+
+    fn description(&self) -> &'static str {
+        "List all the warnings you currently have active"
+    }
+
+    fn help(&self) -> &'static str {
+        "Lists all current warnings you have registered."
+    }
+
+    fn permissions(&self) -> Option<Permissions> {
+        Some(Permissions::ADMINISTRATOR)
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        let event = DiscordListEvent::new(ctx, command);
+        app.list_entries(event).await;
+    }
 }
 
 pub struct DiscordListEvent {
@@ -1,4 +1,133 @@
 pub mod add;
+pub mod export;
+pub mod forget;
 pub mod help;
+pub mod import;
 pub mod list;
 pub mod remove;
+pub mod sync;
+
+use crate::domain::app::App;
+use crate::domain::register::Register;
+use async_trait::async_trait;
+use serenity::all::{CommandInteraction, Context, CreateCommand, CreateCommandOption, Permissions};
+use serenity::prelude::TypeMapKey;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A slash command, everything registering it, running it and listing it in
+/// `/help` needs.
+#[async_trait]
+pub trait SlashCommand<R>: Send + Sync
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str;
+    /// Shown by Discord in the command picker.
+    fn description(&self) -> &'static str;
+    /// Shown in `/help`.
+    fn help(&self) -> &'static str;
+    fn options(&self) -> Vec<CreateCommandOption> {
+        Vec::new()
+    }
+    /// What a member needs to see and use the command, `None` for everyone.
+    fn permissions(&self) -> Option<Permissions> {
+        None
+    }
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction);
+}
+
+/// Every slash command the bot has.
+pub struct Commands<R>(Vec<Box<dyn SlashCommand<R>>>);
+
+impl<R> Commands<R>
+where
+    R: Register + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        let mut commands: Vec<Box<dyn SlashCommand<R>>> = vec![
+            Box::new(add::AddCommand),
+            Box::new(remove::RemoveCommand),
+            Box::new(list::ListCommand),
+            Box::new(export::ExportCommand),
+            Box::new(import::ImportCommand),
+            Box::new(forget::ForgetCommand),
+        ];
+        // Help lists the others, so it comes last.
+        let help = help::HelpCommand::new(&commands);
+        commands.push(Box::new(help));
+
+        Self(commands)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn SlashCommand<R>> {
+        self.0
+            .iter()
+            .find(|command| command.name() == name)
+            .map(Box::as_ref)
+    }
+
+    /// The commands as they are registered with Discord.
+    pub fn definitions(&self) -> Vec<CreateCommand> {
+        self.0
+            .iter()
+            .map(|command| definition(&**command))
+            .collect()
+    }
+}
+
+fn definition<R>(command: &dyn SlashCommand<R>) -> CreateCommand
+where
+    R: Register + Send + Sync,
+{
+    let definition = CreateCommand::new(command.name())
+        .description(command.description())
+        .set_options(command.options());
+
+    match command.permissions() {
+        Some(permissions) => definition.default_member_permissions(permissions),
+        None => definition,
+    }
+}
+
+/// Where the client keeps its commands, for the event handlers to find.
+pub struct Registry<R>(PhantomData<R>);
+
+impl<R> TypeMapKey for Registry<R>
+where
+    R: Register + Send + Sync + 'static,
+{
+    type Value = Arc<Commands<R>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::MockRegister;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_commands() {
+        let commands = Commands::<MockRegister>::new();
+        let names: HashSet<&str> = commands.0.iter().map(|command| command.name()).collect();
+
+        assert_eq!(names.len(), commands.0.len());
+        assert_eq!(commands.definitions().len(), commands.0.len());
+        assert_eq!(commands.get("add").unwrap().name(), "add");
+        assert!(commands.get("nope").is_none());
+    }
+
+    #[test]
+    fn test_definitions() {
+        let commands = Commands::<MockRegister>::new();
+        let definitions = serde_json::to_value(commands.definitions()).unwrap();
+
+        let add = &definitions[0];
+        assert_eq!(add["name"], "add");
+        assert_eq!(add["default_member_permissions"], "8");
+        assert_eq!(add["options"][0]["name"], "bot");
+        let help = &definitions[6];
+        assert_eq!(help["name"], "help");
+        assert!(help.get("default_member_permissions").is_none());
+    }
+}
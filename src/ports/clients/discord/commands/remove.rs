@@ -1,23 +1,53 @@
+use crate::domain::app::App;
 use crate::domain::events::remove::{RemoveEntry, RemoveEvent};
+use crate::domain::register::Register;
+use crate::ports::clients::discord::commands::SlashCommand;
 use crate::ports::clients::discord::utils::messages;
 use async_trait::async_trait;
 use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    Permissions, ResolvedValue, User,
+    CommandInteraction, CommandOptionType, Context, CreateCommandOption, Permissions,
+    ResolvedValue, User,
 };
 
-pub fn register() -> CreateCommand {
-    CreateCommand::new("remove")
-        .description("Remove a bot from the register")
-        .add_option(
+pub struct RemoveCommand;
+
+#[async_trait]
+impl<R> SlashCommand<R> for RemoveCommand
+where
+    R: Register + Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        "remove"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remove a bot from the register"
+    }
+
+    fn help(&self) -> &'static str {
+        "Removes the specified bot from the register."
+    }
+
+    fn options(&self) -> Vec<CreateCommandOption> {
+        vec![
             CreateCommandOption::new(
                 CommandOptionType::User,
                 "bot",
                 "The bot you want to remove from the register",
             )
             .required(true),
-        )
-        .default_member_permissions(Permissions::ADMINISTRATOR)
+        ]
+    }
+
+    fn permissions(&self) -> Option<Permissions> {
+        Some(Permissions::ADMINISTRATOR)
+    }
+
+    async fn run(&self, app: &App<R>, ctx: Context, command: CommandInteraction) {
+        if let Some(event) = RemoveDiscordEvent::new(ctx, command) {
+            app.remove_from_register(event).await;
+        }
+    }
 }
 
 pub struct RemoveDiscordEvent {
@@ -60,11 +90,23 @@ impl RemoveEvent for RemoveDiscordEvent {
         }
     }
 
+    async fn processing_message(&self) {
+        messages::defer_ephemeral(&self.ctx, &self.command).await;
+    }
+
+    async fn not_registered_message(&self) {
+        let message = format!(
+            "{} isn't in your register, so there was nothing to remove",
+            self.bot.name
+        );
+        messages::edit_response(&self.ctx, &self.command, &message).await;
+    }
+
     async fn failed_message(&self) {
-        messages::send_ephemeral(
+        messages::edit_response(
             &self.ctx,
             &self.command,
-            "Failed to remove bot from the register",
+            "Failed to remove bot from the register, please try again later",
         )
         .await;
     }
@@ -74,6 +116,6 @@ impl RemoveEvent for RemoveDiscordEvent {
             "I have removed {} from the register. I will no longer DM you when the bot goes offline or comes online",
             self.bot.name
         );
-        messages::send_ephemeral(&self.ctx, &self.command, &message).await;
+        messages::edit_response(&self.ctx, &self.command, &message).await;
     }
 }
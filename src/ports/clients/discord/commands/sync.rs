@@ -0,0 +1,309 @@
+use serde_json::Value;
+use serenity::Error;
+use serenity::all::{Command, CommandId, CreateCommand, GuildId, Http};
+use serenity::prelude::TypeMapKey;
+
+/// Compared on every command, a field missing on one side counts as its default.
+const COMPARED_FIELDS: [&str; 8] = [
+    "name",
+    "description",
+    "options",
+    "type",
+    "default_member_permissions",
+    "nsfw",
+    "name_localizations",
+    "description_localizations",
+];
+/// Compared only when the local definition sets them, Discord fills in its own
+/// defaults otherwise.
+const COMPARED_IF_SET: [&str; 4] = ["dm_permission", "integration_types", "contexts", "handler"];
+/// Discord's value for `type` on a slash command.
+const CHAT_INPUT: u64 = 1;
+
+/// The guild commands are synced to instead of globally, if any.
+pub struct CommandsGuild;
+
+impl TypeMapKey for CommandsGuild {
+    type Value = Option<GuildId>;
+}
+
+/// What it takes to make Discord's commands match the local definitions.
+#[derive(Debug, PartialEq)]
+pub enum CommandChange<'a> {
+    Create(&'a str),
+    Edit(CommandId, &'a str),
+    Delete(CommandId, String),
+}
+
+/// Syncs the commands to a guild, or globally when there is none, only
+/// creating, editing or deleting what differs from what Discord already has.
+pub async fn sync_commands(http: &Http, guild: Option<GuildId>, local: &[CreateCommand]) {
+    if guild.is_some() {
+        warn_of_global_commands(http).await;
+    }
+
+    let remote = match guild {
+        Some(guild) => guild.get_commands(http).await,
+        None => Command::get_global_commands(http).await,
+    };
+    let remote = match remote {
+        Ok(remote) => remote,
+        Err(why) => {
+            tracing::warn!("Could not fetch existing commands, not syncing them - {why:?}");
+            return;
+        }
+    };
+
+    let definitions: Vec<(String, Value)> = local.iter().map(definition).collect();
+    let changes = diff(&definitions, &remote);
+    if changes.is_empty() {
+        tracing::info!("Commands are up to date");
+        return;
+    }
+
+    let command = |name: &str| {
+        definitions
+            .iter()
+            .position(|(local, _)| local == name)
+            .map(|index| local[index].clone())
+            .unwrap_or_else(|| CreateCommand::new(name))
+    };
+    for change in changes {
+        let result = match &change {
+            CommandChange::Create(name) => create(http, guild, command(name)).await,
+            CommandChange::Edit(id, name) => edit(http, guild, *id, command(name)).await,
+            CommandChange::Delete(id, _) => delete(http, guild, *id).await,
+        };
+
+        match result {
+            Ok(()) => tracing::info!("Synced command - {change:?}"),
+            Err(why) => tracing::warn!("Could not sync command - {change:?} - {why:?}"),
+        }
+    }
+}
+
+/// Global commands from an earlier global sync are not removed by a guild
+/// sync, and show up next to the guild's own, so every command is listed twice.
+/// They are left alone as the same application may be running globally.
+async fn warn_of_global_commands(http: &Http) {
+    match Command::get_global_commands(http).await {
+        Ok(global) if !global.is_empty() => tracing::warn!(
+            "{} global command(s) are still registered, they show up twice in the commands guild",
+            global.len()
+        ),
+        Ok(_) => {}
+        Err(why) => tracing::warn!("Could not check for global commands - {why:?}"),
+    }
+}
+
+async fn create(http: &Http, guild: Option<GuildId>, command: CreateCommand) -> Result<(), Error> {
+    match guild {
+        Some(guild) => guild.create_command(http, command).await.map(drop),
+        None => Command::create_global_command(http, command)
+            .await
+            .map(drop),
+    }
+}
+
+async fn edit(
+    http: &Http,
+    guild: Option<GuildId>,
+    id: CommandId,
+    command: CreateCommand,
+) -> Result<(), Error> {
+    match guild {
+        Some(guild) => guild.edit_command(http, id, command).await.map(drop),
+        None => Command::edit_global_command(http, id, command)
+            .await
+            .map(drop),
+    }
+}
+
+async fn delete(http: &Http, guild: Option<GuildId>, id: CommandId) -> Result<(), Error> {
+    match guild {
+        Some(guild) => guild.delete_command(http, id).await,
+        None => Command::delete_global_command(http, id).await,
+    }
+}
+
+fn definition(command: &CreateCommand) -> (String, Value) {
+    let mut value = serde_json::to_value(command).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.entry("type").or_insert(Value::from(CHAT_INPUT));
+    }
+    let name = value["name"].as_str().unwrap_or_default().to_string();
+
+    (name, value)
+}
+
+/// Matches commands up by name, in the order the changes should be made.
+pub fn diff<'a>(local: &'a [(String, Value)], remote: &[Command]) -> Vec<CommandChange<'a>> {
+    let mut changes = Vec::new();
+
+    for (name, definition) in local {
+        match remote.iter().find(|command| &command.name == name) {
+            None => changes.push(CommandChange::Create(name)),
+            Some(command) => {
+                let existing = serde_json::to_value(command).unwrap_or_default();
+                if !is_same(definition, &existing) {
+                    changes.push(CommandChange::Edit(command.id, name));
+                }
+            }
+        }
+    }
+
+    for command in remote {
+        if !local.iter().any(|(name, _)| name == &command.name) {
+            changes.push(CommandChange::Delete(command.id, command.name.clone()));
+        }
+    }
+
+    changes
+}
+
+fn is_same(local: &Value, remote: &Value) -> bool {
+    let compared = COMPARED_FIELDS.iter().chain(
+        COMPARED_IF_SET
+            .iter()
+            .filter(|field| local.get(**field).is_some()),
+    );
+
+    compared
+        .into_iter()
+        .all(|field| normalize(local.get(*field)) == normalize(remote.get(*field)))
+}
+
+/// Drops what Discord and the builders leave out or send empty interchangeably:
+/// nulls, `false` and empty lists and maps.
+fn normalize(value: Option<&Value>) -> Value {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => Value::Null,
+        Some(Value::Array(items)) if items.is_empty() => Value::Null,
+        Some(Value::Array(items)) => items.iter().map(|item| normalize(Some(item))).collect(),
+        Some(Value::Object(fields)) => {
+            let fields: serde_json::Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), normalize(Some(value))))
+                .filter(|(_, value)| !value.is_null())
+                .collect();
+            if fields.is_empty() {
+                Value::Null
+            } else {
+                Value::Object(fields)
+            }
+        }
+        Some(value) => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use serenity::all::{CommandOptionType, CreateCommandOption, Permissions};
+
+    /// A command the way Discord returns it.
+    fn remote(id: u64, name: &str, description: &str, extra: Value) -> Command {
+        let mut command = json!({
+            "id": id.to_string(),
+            "type": 1,
+            "application_id": "1",
+            "name": name,
+            "name_localizations": null,
+            "description": description,
+            "description_localizations": null,
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "nsfw": false,
+            "integration_types": [0],
+            "contexts": null,
+            "version": "1",
+        });
+        command
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(command).unwrap()
+    }
+
+    fn add() -> CreateCommand {
+        CreateCommand::new("add")
+            .description("Add a bot")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "bot", "The bot").required(true),
+            )
+    }
+
+    fn list() -> CreateCommand {
+        CreateCommand::new("list")
+            .description("List bots")
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+    }
+
+    fn definitions(commands: &[CreateCommand]) -> Vec<(String, Value)> {
+        commands.iter().map(definition).collect()
+    }
+
+    fn add_option() -> Value {
+        json!({ "options": [{
+            "type": 6,
+            "name": "bot",
+            "description": "The bot",
+            "required": true,
+        }]})
+    }
+
+    #[test]
+    fn test_unchanged_commands() {
+        let local = definitions(&[add(), list()]);
+        let remote = [
+            remote(10, "add", "Add a bot", add_option()),
+            remote(
+                11,
+                "list",
+                "List bots",
+                json!({ "default_member_permissions": "8" }),
+            ),
+        ];
+
+        assert_eq!(diff(&local, &remote), Vec::new());
+    }
+
+    #[test]
+    fn test_changed_commands() {
+        let local = definitions(&[add(), list()]);
+        let remote = [
+            remote(10, "add", "Add a bot", json!({})),
+            remote(11, "list", "List bots", json!({})),
+            remote(12, "old", "Removed from the code", json!({})),
+        ];
+
+        assert_eq!(
+            diff(&local, &remote),
+            vec![
+                CommandChange::Edit(CommandId::new(10), "add"),
+                CommandChange::Edit(CommandId::new(11), "list"),
+                CommandChange::Delete(CommandId::new(12), String::from("old")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_command() {
+        let local = definitions(&[add(), list()]);
+        let remote = [remote(10, "add", "Add a bot", add_option())];
+
+        assert_eq!(diff(&local, &remote), vec![CommandChange::Create("list")]);
+    }
+
+    #[test]
+    fn test_description_change() {
+        let local = definitions(&[add()]);
+        let remote = [remote(10, "add", "Add a bot to a register", add_option())];
+
+        assert_eq!(
+            diff(&local, &remote),
+            vec![CommandChange::Edit(CommandId::new(10), "add")]
+        );
+    }
+}
@@ -1,12 +1,49 @@
 use crate::domain::events::status::{BotStates, StatusEvent};
 use crate::domain::register::RegisterEntry;
+use crate::metrics::metrics;
 use crate::ports::clients::discord::utils::user::user_from_id;
 use async_trait::async_trait;
 use serenity::all::{
-    Context, CreateMessage, MessageBuilder, OnlineStatus, Presence, PresenceUser, UserId,
+    CacheHttp, Context, CreateMessage, HttpError, MessageBuilder, OnlineStatus, Presence,
+    PresenceUser, UserId,
 };
 use std::str::FromStr;
 
+/// Discord's error codes for "Unknown User" and "Cannot send messages to this user".
+const UNDELIVERABLE_CODES: [isize; 2] = [10013, 50007];
+
+/// The transport label of notifications sent as Discord DMs.
+const DM_TRANSPORT: &str = "discord_dm";
+
+/// DMs a user, returning false if Discord will never deliver messages to them.
+pub async fn direct_message(cache_http: impl CacheHttp, user_id: UserId, message: String) -> bool {
+    let sent = user_id
+        .direct_message(cache_http, CreateMessage::new().content(message))
+        .await;
+    let outcome = if sent.is_ok() { "sent" } else { "failed" };
+    metrics()
+        .notifications
+        .with_label_values(&[DM_TRANSPORT, outcome])
+        .inc();
+
+    match sent {
+        Ok(_) => true,
+        Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+            if UNDELIVERABLE_CODES.contains(&response.error.code) =>
+        {
+            tracing::info!(
+                "Could not deliver message to a user - {}",
+                response.error.message
+            );
+            false
+        }
+        Err(why) => {
+            tracing::warn!("Could not send message to Discord: {why}");
+            true
+        }
+    }
+}
+
 pub struct DiscordStatusEvent {
     bot: PresenceUser,
     status: OnlineStatus,
@@ -49,7 +86,8 @@ impl StatusEvent for DiscordStatusEvent {
         }
     }
 
-    async fn send_offline_warning(&self, entries: Vec<RegisterEntry>) {
+    async fn send_offline_warning(&self, entries: Vec<RegisterEntry>) -> Vec<String> {
+        let mut undeliverable = Vec::new();
         for entry in entries {
             let user_id = UserId::new(u64::from_str(&entry.user_id).unwrap());
             let bot_id = UserId::new(u64::from_str(&entry.bot_id).unwrap());
@@ -67,16 +105,16 @@ impl StatusEvent for DiscordStatusEvent {
                 .push(" has gone offline!")
                 .build();
 
-            if let Err(why) = user_id
-                .direct_message(&self.ctx, CreateMessage::new().content(message))
-                .await
-            {
-                log::warn!("Could not send message to Discord: {why}");
+            if !direct_message(&self.ctx, user_id, message).await {
+                undeliverable.push(entry.user_id);
             }
         }
+
+        undeliverable
     }
 
-    async fn send_online_message(&self, entries: Vec<RegisterEntry>) {
+    async fn send_online_message(&self, entries: Vec<RegisterEntry>) -> Vec<String> {
+        let mut undeliverable = Vec::new();
         for entry in entries {
             let user_id = UserId::new(u64::from_str(&entry.user_id).unwrap());
             let bot_id = UserId::new(u64::from_str(&entry.bot_id).unwrap());
@@ -96,12 +134,11 @@ impl StatusEvent for DiscordStatusEvent {
                 .push(" is back online!")
                 .build();
 
-            if let Err(why) = user_id
-                .direct_message(&self.ctx, CreateMessage::new().content(message))
-                .await
-            {
-                log::warn!("Could not send message to Discord: {why}");
+            if !direct_message(&self.ctx, user_id, message).await {
+                undeliverable.push(entry.user_id);
             }
         }
+
+        undeliverable
     }
 }
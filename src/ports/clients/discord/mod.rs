@@ -1,3 +1,4 @@
+mod cleanup;
 pub mod client;
 mod commands;
 mod event;
@@ -1,5 +1,7 @@
 use serenity::all::{
-    CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+    CommandInteraction, ComponentInteraction, Context, CreateActionRow, CreateAttachment,
+    CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse,
 };
 
 pub async fn send_ephemeral(ctx: &Context, command: &CommandInteraction, message: &str) {
@@ -10,6 +12,68 @@ pub async fn send_ephemeral(ctx: &Context, command: &CommandInteraction, message
         .create_response(ctx, CreateInteractionResponse::Message(response))
         .await
     {
-        log::warn!("Error sending message: {why:?}");
+        tracing::warn!("Error sending message: {why:?}");
+    }
+}
+
+pub async fn send_ephemeral_with_buttons(
+    ctx: &Context,
+    command: &CommandInteraction,
+    message: &str,
+    buttons: Vec<CreateButton>,
+) {
+    let response = CreateInteractionResponseMessage::new()
+        .content(message)
+        .components(vec![CreateActionRow::Buttons(buttons)])
+        .ephemeral(true);
+    if let Err(why) = command
+        .create_response(ctx, CreateInteractionResponse::Message(response))
+        .await
+    {
+        tracing::warn!("Error sending message: {why:?}");
+    }
+}
+
+pub async fn defer_ephemeral(ctx: &Context, command: &CommandInteraction) {
+    if let Err(why) = command.defer_ephemeral(ctx).await {
+        tracing::warn!("Error deferring response: {why:?}");
+    }
+}
+
+pub async fn edit_response(ctx: &Context, command: &CommandInteraction, message: &str) {
+    let response = EditInteractionResponse::new().content(message);
+    if let Err(why) = command.edit_response(ctx, response).await {
+        tracing::warn!("Error editing response: {why:?}");
+    }
+}
+
+pub async fn edit_response_with_file(
+    ctx: &Context,
+    command: &CommandInteraction,
+    message: &str,
+    file: CreateAttachment,
+) {
+    let response = EditInteractionResponse::new()
+        .content(message)
+        .new_attachment(file);
+    if let Err(why) = command.edit_response(ctx, response).await {
+        tracing::warn!("Error editing response: {why:?}");
+    }
+}
+
+/// Replaces the message a button was clicked on, removing its buttons.
+pub async fn update_component_message(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    message: &str,
+) {
+    let response = CreateInteractionResponseMessage::new()
+        .content(message)
+        .components(Vec::new());
+    if let Err(why) = component
+        .create_response(ctx, CreateInteractionResponse::UpdateMessage(response))
+        .await
+    {
+        tracing::warn!("Error updating message: {why:?}");
     }
 }
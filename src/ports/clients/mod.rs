@@ -1,15 +1,36 @@
+use crate::config::DiscordConfig;
 use crate::domain::app::App;
 use crate::domain::register::Register;
 use crate::ports::clients::discord::client::DiscordClient;
 use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
 
 pub mod discord;
 
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Could not create the client - {0}")]
+    Create(#[source] serenity::Error),
+    #[error("Could not start the client - {0}")]
+    Start(#[source] serenity::Error),
+}
+
 #[async_trait]
 pub trait Client {
-    async fn run(&mut self);
+    /// Runs until `shutdown` completes and the events in flight have been
+    /// handled, or the shutdown timeout has passed. Fails when the client
+    /// could not connect.
+    async fn run<S>(&mut self, shutdown: S) -> Result<(), ClientError>
+    where
+        S: Future<Output = ()> + Send + 'static;
 }
 
-pub async fn init_client<R: Register + Send + Sync + 'static>(app: App<R>) -> impl Client {
-    DiscordClient::new(app).await
+pub async fn init_client<R: Register + Send + Sync + 'static>(
+    app: Arc<App<R>>,
+    config: &DiscordConfig,
+    shutdown_timeout: Duration,
+) -> Result<impl Client, ClientError> {
+    DiscordClient::new(app, config, shutdown_timeout).await
 }
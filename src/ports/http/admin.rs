@@ -0,0 +1,516 @@
+use crate::domain::app::App;
+use crate::domain::events::create::CreateEntry;
+use crate::domain::events::list::ListEntriesPayload;
+use crate::domain::events::remove::RemoveEntry;
+use crate::domain::incidents::{BotStatus, Incident};
+use crate::domain::register::{Register, RegisterEntry, RegisterError};
+use axum::extract::{Path, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::SystemTime;
+use thiserror::Error;
+
+/// The OpenAPI description of the admin API, served at `/api/openapi.json`.
+const OPENAPI: &str = include_str!("openapi.json");
+
+#[derive(Error, Debug)]
+enum ApiError {
+    #[error("`{0}` is not a Discord ID")]
+    InvalidId(String),
+    #[error("The bot has not been seen since the monitor started")]
+    Unseen,
+    #[error("The monitor is shutting down")]
+    ShuttingDown,
+    #[error(transparent)]
+    Register(#[from] RegisterError),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::InvalidId(_) => StatusCode::BAD_REQUEST,
+            Self::Unseen | Self::Register(RegisterError::NotRegistered) => StatusCode::NOT_FOUND,
+            Self::Register(RegisterError::AlreadyRegistered) => StatusCode::CONFLICT,
+            Self::ShuttingDown => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Register(why) if why.is_retryable() => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Register(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        if status.is_server_error() {
+            tracing::warn!("Admin API request failed - {self}");
+        }
+
+        (
+            status,
+            Json(ErrorBody {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Registration {
+    user_id: String,
+    bot_id: String,
+}
+
+impl From<RegisterEntry> for Registration {
+    fn from(entry: RegisterEntry) -> Self {
+        Self {
+            user_id: entry.user_id,
+            bot_id: entry.bot_id,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    bot_id: String,
+    status: &'static str,
+    since: u64,
+}
+
+#[derive(Serialize)]
+struct IncidentBody {
+    started_at: u64,
+    ended_at: Option<u64>,
+}
+
+impl From<Incident> for IncidentBody {
+    fn from(incident: Incident) -> Self {
+        Self {
+            started_at: unix_secs(incident.started_at),
+            ended_at: incident.ended_at.map(unix_secs),
+        }
+    }
+}
+
+/// The admin API, every route but the OpenAPI description needs one of `tokens`
+/// as a bearer token.
+pub fn router<R>(app: Arc<App<R>>, tokens: Vec<String>) -> Router
+where
+    R: Register + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/users/{user_id}/registrations", get(list::<R>))
+        .route(
+            "/users/{user_id}/registrations/{bot_id}",
+            delete(remove::<R>),
+        )
+        .route("/bots/{bot_id}/registrations", get(fetch::<R>))
+        .route("/bots/{bot_id}/status", get(status::<R>))
+        .route("/bots/{bot_id}/incidents", get(incidents::<R>))
+        .route("/registrations", post(add::<R>))
+        .with_state(app)
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(tokens),
+            authenticate,
+        ))
+        .route("/openapi.json", get(openapi))
+}
+
+async fn authenticate(
+    State(tokens): State<Arc<Vec<String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match presented {
+        Some(presented) if tokens.iter().any(|token| matches(token, presented)) => {
+            next.run(request).await
+        }
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ErrorBody {
+                error: String::from("A valid API token is required"),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Compares every byte whatever the first difference is, so the time taken does
+/// not give away how much of a token was guessed.
+fn matches(token: &str, presented: &str) -> bool {
+    token.len() == presented.len()
+        && token
+            .bytes()
+            .zip(presented.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn openapi() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI)
+}
+
+async fn list<R>(
+    State(app): State<Arc<App<R>>>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<Registration>>, ApiError>
+where
+    R: Register,
+{
+    let payload = ListEntriesPayload {
+        user_id: discord_id(user_id)?,
+    };
+    let entries = app.register.list(payload).await?;
+
+    Ok(Json(entries.into_iter().map(Registration::from).collect()))
+}
+
+async fn fetch<R>(
+    State(app): State<Arc<App<R>>>,
+    Path(bot_id): Path<String>,
+) -> Result<Json<Vec<Registration>>, ApiError>
+where
+    R: Register,
+{
+    let entries = app.register.fetch(discord_id(bot_id)?).await?;
+
+    Ok(Json(entries.into_iter().map(Registration::from).collect()))
+}
+
+/// Unlike `/add` this does not check the bot is one, that would need Discord.
+/// A user registered by mistake is harmless: alerts are only sent for bots.
+async fn add<R>(
+    State(app): State<Arc<App<R>>>,
+    Json(registration): Json<Registration>,
+) -> Result<(StatusCode, Json<Registration>), ApiError>
+where
+    R: Register,
+{
+    let Some(_guard) = app.in_flight.enter() else {
+        return Err(ApiError::ShuttingDown);
+    };
+    let entry = CreateEntry {
+        user_id: discord_id(registration.user_id)?,
+        bot_id: discord_id(registration.bot_id)?,
+        version: 0,
+    };
+    let (user_id, bot_id) = (entry.user_id.clone(), entry.bot_id.clone());
+
+    app.register.add(entry).await?;
+    tracing::info!(%bot_id, "Added entry through the admin API");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Registration::from(RegisterEntry { bot_id, user_id })),
+    ))
+}
+
+async fn remove<R>(
+    State(app): State<Arc<App<R>>>,
+    Path((user_id, bot_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError>
+where
+    R: Register,
+{
+    let Some(_guard) = app.in_flight.enter() else {
+        return Err(ApiError::ShuttingDown);
+    };
+    let entry = RemoveEntry {
+        user_id: discord_id(user_id)?,
+        bot_id: discord_id(bot_id)?,
+    };
+    let bot_id = entry.bot_id.clone();
+
+    app.register.remove(entry).await?;
+    tracing::info!(%bot_id, "Removed entry through the admin API");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn status<R>(
+    State(app): State<Arc<App<R>>>,
+    Path(bot_id): Path<String>,
+) -> Result<Json<Status>, ApiError>
+where
+    R: Register,
+{
+    let bot_id = discord_id(bot_id)?;
+    let (status, since) = app.statuses.status(&bot_id).ok_or(ApiError::Unseen)?;
+
+    Ok(Json(Status {
+        bot_id,
+        status: match status {
+            BotStatus::Online => "online",
+            BotStatus::Offline => "offline",
+        },
+        since: unix_secs(since),
+    }))
+}
+
+async fn incidents<R>(
+    State(app): State<Arc<App<R>>>,
+    Path(bot_id): Path<String>,
+) -> Result<Json<Vec<IncidentBody>>, ApiError>
+where
+    R: Register,
+{
+    let incidents = app.statuses.incidents(&discord_id(bot_id)?);
+
+    Ok(Json(
+        incidents.into_iter().map(IncidentBody::from).collect(),
+    ))
+}
+
+/// Users and bots are both keyed by their Discord snowflake.
+fn discord_id(id: String) -> Result<String, ApiError> {
+    match id.parse::<u64>() {
+        Ok(_) => Ok(id),
+        Err(_) => Err(ApiError::InvalidId(id)),
+    }
+}
+
+fn unix_secs(at: SystemTime) -> u64 {
+    at.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::register::{MockRegister, StorageError};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use mockall::predicate::*;
+    use serde_json::{Value, json};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "0123456789abcdef";
+    const USER_ID: &str = "123456789012345678";
+    const BOT_ID: &str = "876543210987654321";
+
+    fn admin(register: MockRegister) -> Router {
+        router(Arc::new(App::new(register)), vec![String::from(TOKEN)])
+    }
+
+    fn request(method: &str, uri: &str, body: Option<Value>) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {TOKEN}"));
+        match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+            None => request.body(Body::empty()).unwrap(),
+        }
+    }
+
+    async fn send(router: Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    fn entry() -> RegisterEntry {
+        RegisterEntry {
+            bot_id: String::from(BOT_ID),
+            user_id: String::from(USER_ID),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let router = admin(MockRegister::new());
+        let uri = format!("/users/{USER_ID}/registrations");
+
+        let unauthenticated = Request::get(&uri).body(Body::empty()).unwrap();
+        let (status, _) = send(router.clone(), unauthenticated).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let wrong_token = Request::get(&uri)
+            .header(header::AUTHORIZATION, "Bearer fedcba9876543210")
+            .body(Body::empty())
+            .unwrap();
+        let (status, _) = send(router.clone(), wrong_token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let openapi = Request::get("/openapi.json").body(Body::empty()).unwrap();
+        let (status, _) = send(router, openapi).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let mut register = MockRegister::new();
+        register
+            .expect_list()
+            .times(1)
+            .returning(|_| Ok(vec![entry()]));
+
+        let uri = format!("/users/{USER_ID}/registrations");
+        let (status, body) = send(admin(register), request("GET", &uri, None)).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!([{ "user_id": USER_ID, "bot_id": BOT_ID }]));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_id() {
+        let (status, body) = send(
+            admin(MockRegister::new()),
+            request("GET", "/bots/not-a-bot/registrations", None),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("not-a-bot"));
+    }
+
+    #[tokio::test]
+    async fn test_add() {
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .with(eq(CreateEntry {
+                user_id: String::from(USER_ID),
+                bot_id: String::from(BOT_ID),
+                version: 0,
+            }))
+            .return_const(Ok(()));
+
+        let body = json!({ "user_id": USER_ID, "bot_id": BOT_ID });
+        let (status, _) = send(
+            admin(register),
+            request("POST", "/registrations", Some(body)),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn test_add_already_registered() {
+        let mut register = MockRegister::new();
+        register
+            .expect_add()
+            .times(1)
+            .return_const(Err(RegisterError::AlreadyRegistered));
+
+        let body = json!({ "user_id": USER_ID, "bot_id": BOT_ID });
+        let (status, _) = send(
+            admin(register),
+            request("POST", "/registrations", Some(body)),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_add_shutting_down() {
+        let mut register = MockRegister::new();
+        register.expect_add().times(0);
+
+        let app = Arc::new(App::new(register));
+        assert!(app.in_flight.drain(Duration::ZERO).await);
+
+        let body = json!({ "user_id": USER_ID, "bot_id": BOT_ID });
+        let (status, _) = send(
+            router(app, vec![String::from(TOKEN)]),
+            request("POST", "/registrations", Some(body)),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_update_not_routed() {
+        let mut register = MockRegister::new();
+        register.expect_update().times(0);
+
+        let body = json!({ "user_id": USER_ID, "bot_id": BOT_ID });
+        let (status, _) = send(
+            admin(register),
+            request("PUT", "/registrations", Some(body)),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn test_remove_unavailable() {
+        let mut register = MockRegister::new();
+        register
+            .expect_remove()
+            .times(1)
+            .return_const(Err(RegisterError::EntryRemoveError(
+                StorageError::retryable("throttled"),
+            )));
+
+        let uri = format!("/users/{USER_ID}/registrations/{BOT_ID}");
+        let (status, _) = send(admin(register), request("DELETE", &uri, None)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_status_and_incidents() {
+        let app = Arc::new(App::new(MockRegister::new()));
+        let router = router(app.clone(), vec![String::from(TOKEN)]);
+        let went_offline = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(100);
+
+        let uri = format!("/bots/{BOT_ID}/status");
+        let (status, _) = send(router.clone(), request("GET", &uri, None)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        app.statuses
+            .record(BOT_ID, BotStatus::Offline, went_offline);
+        let (status, body) = send(router.clone(), request("GET", &uri, None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            json!({ "bot_id": BOT_ID, "status": "offline", "since": 100 })
+        );
+
+        let uri = format!("/bots/{BOT_ID}/incidents");
+        let (status, body) = send(router, request("GET", &uri, None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!([{ "started_at": 100, "ended_at": null }]));
+    }
+
+    #[test]
+    fn test_openapi_covers_routes() {
+        let openapi: Value = serde_json::from_str(OPENAPI).unwrap();
+        let paths = openapi["paths"].as_object().unwrap();
+
+        for path in [
+            "/api/users/{user_id}/registrations",
+            "/api/users/{user_id}/registrations/{bot_id}",
+            "/api/bots/{bot_id}/registrations",
+            "/api/bots/{bot_id}/status",
+            "/api/bots/{bot_id}/incidents",
+            "/api/registrations",
+        ] {
+            assert!(paths.contains_key(path), "{path} is not documented");
+        }
+    }
+}
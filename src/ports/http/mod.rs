@@ -0,0 +1,149 @@
+use crate::health::health;
+use crate::metrics::metrics;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use tokio::net::TcpListener;
+
+pub mod admin;
+pub mod probe;
+
+/// The content type of the Prometheus text format.
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// `admin` is served under `/api` when the admin API is turned on.
+fn router(admin: Option<Router>) -> Router {
+    let router = Router::new()
+        .route("/metrics", get(serve_metrics))
+        .route("/healthz", get(serve_health))
+        .route("/readyz", get(serve_readiness));
+
+    match admin {
+        Some(admin) => router.nest("/api", admin),
+        None => router,
+    }
+}
+
+async fn serve_health() -> &'static str {
+    "ok"
+}
+
+async fn serve_readiness() -> impl IntoResponse {
+    let readiness = health().readiness();
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(readiness))
+}
+
+async fn serve_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        metrics().encode(),
+    )
+}
+
+pub async fn serve(listener: TcpListener, admin: Option<Router>) {
+    if let Err(why) = axum::serve(listener, router(admin)).await {
+        tracing::error!("HTTP server stopped - {why}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics() {
+        metrics().presence_updates.inc();
+
+        let response = router(None)
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("bot_monitor_presence_updates_total"));
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let response = router(None)
+            .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_not_ready_before_gateway() {
+        let response = router(None)
+            .oneshot(Request::get("/readyz").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("\"gateway\":false"));
+    }
+
+    #[tokio::test]
+    async fn test_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, None));
+
+        assert!(probe::probe(addr, "/healthz").await.is_ok());
+        assert!(matches!(
+            probe::probe(addr, "/readyz").await,
+            Err(probe::ProbeError::Unhealthy { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admin_nested() {
+        let app = std::sync::Arc::new(crate::domain::app::App::new(
+            crate::domain::register::MockRegister::new(),
+        ));
+        let admin = admin::router(app, vec![String::from("0123456789abcdef")]);
+
+        let response = router(Some(admin))
+            .oneshot(
+                Request::get("/api/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router(None)
+            .oneshot(
+                Request::get("/api/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path() {
+        let response = router(None)
+            .oneshot(Request::get("/nope").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
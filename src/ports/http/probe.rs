@@ -0,0 +1,56 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum ProbeError {
+    #[error("Could not reach {0} - {1}")]
+    Unreachable(SocketAddr, std::io::Error),
+    #[error("No response from {0} within {PROBE_TIMEOUT:?}")]
+    Timeout(SocketAddr),
+    #[error("{path} answered {status_line:?}")]
+    Unhealthy { path: String, status_line: String },
+}
+
+/// Asks the bot's own HTTP server for `path`, succeeding on a 200. A bot
+/// listening on every interface is probed over loopback.
+pub async fn probe(addr: SocketAddr, path: &str) -> Result<(), ProbeError> {
+    let mut addr = addr;
+    if addr.ip().is_unspecified() {
+        addr.set_ip(if addr.is_ipv4() {
+            Ipv4Addr::LOCALHOST.into()
+        } else {
+            Ipv6Addr::LOCALHOST.into()
+        });
+    }
+
+    let status_line = tokio::time::timeout(PROBE_TIMEOUT, get(addr, path))
+        .await
+        .map_err(|_| ProbeError::Timeout(addr))?
+        .map_err(|why| ProbeError::Unreachable(addr, why))?;
+
+    if status_line.split_whitespace().nth(1) == Some("200") {
+        Ok(())
+    } else {
+        Err(ProbeError::Unhealthy {
+            path: String::from(path),
+            status_line,
+        })
+    }
+}
+
+async fn get(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    let request = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+
+    Ok(response.lines().next().unwrap_or_default().to_string())
+}
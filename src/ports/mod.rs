@@ -1 +1,3 @@
+pub mod cli;
 pub mod clients;
+pub mod http;